@@ -20,7 +20,7 @@ pub enum LuckSnakeError {
     UnableToGenerateUniqueNumber,
     
     /// 无效的数字范围
-    /// 当查询的数字超出0-999范围时触发
+    /// 当初始化时数字范围的最小值大于最大值，或范围内的数字个数超过MAX_NUMBERS时触发
     #[msg("Invalid number range")]
     InvalidNumberRange,
     
@@ -43,4 +43,289 @@ pub enum LuckSnakeError {
     /// 当用户账户中的数字数量达到上限时触发
     #[msg("User account full")]
     UserAccountFull,
+    
+    /// 揭示过早
+    /// 当提交后经过的slot数不足揭示延迟时触发
+    #[msg("Reveal too early")]
+    RevealTooEarly,
+    
+    /// 承诺已过期
+    /// 当承诺超过有效期后尝试揭示时触发，此时只能申请退款
+    #[msg("Commitment expired")]
+    CommitmentExpired,
+    
+    /// 承诺尚未过期
+    /// 当承诺仍在有效期内且可以揭示时尝试退款触发
+    #[msg("Commitment not expired")]
+    CommitmentNotExpired,
+    
+    /// 揭示的秘密值与承诺不符
+    /// 当秘密值的哈希不等于提交的承诺时触发
+    #[msg("Revealed secret does not match commitment")]
+    InvalidReveal,
+    
+    /// 找不到区块哈希
+    /// 当SlotHashes sysvar中没有所需slot的区块哈希时触发
+    #[msg("Slot hash not found")]
+    SlotHashNotFound,
+    
+    /// 预言机未配置
+    /// 当配置中没有设置预言机程序时请求随机数触发
+    #[msg("Oracle not configured")]
+    OracleNotConfigured,
+    
+    /// 随机数尚未回填
+    /// 当预言机还没有回填随机数时尝试完成生成触发
+    #[msg("VRF request not fulfilled")]
+    VrfRequestNotFulfilled,
+    
+    /// 随机数已回填
+    /// 当预言机重复回填，或请求已回填且仍可完成时尝试取消触发
+    #[msg("VRF request already fulfilled")]
+    VrfRequestAlreadyFulfilled,
+    
+    /// 请求已超时
+    /// 当预言机在超时后尝试回填时触发
+    #[msg("VRF request timed out")]
+    VrfRequestTimedOut,
+    
+    /// 请求尚未超时
+    /// 当请求仍在等待预言机回填时尝试取消触发
+    #[msg("VRF request not timed out")]
+    VrfRequestNotTimedOut,
+    
+    /// 用户账户中仍有数字
+    /// 当用户还持有数字时尝试关闭用户账户触发
+    #[msg("User account still holds numbers")]
+    UserAccountNotEmpty,
+    
+    /// 无效的生成价格
+    /// 当管理员设置的价格超出允许范围时触发
+    #[msg("Invalid generation price")]
+    InvalidGenerationPrice,
+    
+    /// 没有进行中的管理员转移
+    /// 当没有待接受的管理员时尝试接受或取消转移触发
+    #[msg("No pending authority transfer")]
+    NoPendingAuthority,
+    
+    /// 无效的新管理员地址
+    /// 当提议的新管理员为默认地址或与当前管理员相同时触发
+    #[msg("Invalid new authority")]
+    InvalidNewAuthority,
+    
+    /// 无效的配置账户
+    /// 当迁移的账户不是本程序的配置账户时触发
+    #[msg("Invalid config account")]
+    InvalidConfigAccount,
+    
+    /// 无效的提取金额
+    /// 当提取金额为0时触发
+    #[msg("Invalid withdrawal amount")]
+    InvalidWithdrawalAmount,
+    
+    /// 提取金额超过可提取资金
+    /// 当提取后财库余额将低于免租金最低余额时触发
+    #[msg("Withdrawal amount exceeds available funds")]
+    InsufficientTreasuryFunds,
+    
+    /// 生成已暂停
+    /// 当管理员或守护者暂停生成后尝试生成数字时触发
+    #[msg("Generation is paused")]
+    GenerationPaused,
+    
+    /// 无效的奖金份额
+    /// 当奖级份额为0或各奖级份额之和超过10,000基点时触发
+    #[msg("Invalid prize share")]
+    InvalidPrizeShare,
+    
+    /// 无效的轮次截止时间
+    /// 当开启轮次时截止时间不晚于当前时间触发
+    #[msg("Invalid round close time")]
+    InvalidRoundCloseTime,
+    
+    /// 已有进行中的轮次
+    /// 当上一轮尚未开奖时尝试开启新一轮触发
+    #[msg("A round is already in progress")]
+    RoundInProgress,
+    
+    /// 轮次已截止
+    /// 当轮次截止之后、开奖之前尝试发放数字或请求预言机随机数，或提交生成请求时距离截止不足揭示所需的时间触发
+    #[msg("Round is closed")]
+    RoundClosed,
+    
+    /// 轮次尚未截止
+    /// 当截止时间之前尝试开奖时触发
+    #[msg("Round is not closed yet")]
+    RoundNotClosed,
+    
+    /// 轮次已开奖
+    /// 当重复开奖时触发
+    #[msg("Round already drawn")]
+    RoundAlreadyDrawn,
+    
+    /// 轮次尚未开奖
+    /// 当开奖之前尝试领取奖金时触发
+    #[msg("Round not drawn yet")]
+    RoundNotDrawn,
+    
+    /// 没有可领取的奖金
+    /// 当中奖数字在开奖时尚未发放时尝试领取触发
+    #[msg("No prize to claim")]
+    NoPrize,
+    
+    /// 奖金已被领取
+    /// 当重复领取奖金时触发
+    #[msg("Prize already claimed")]
+    PrizeAlreadyClaimed,
+    
+    /// 无效的奖级表
+    /// 当奖级数量超过上限、匹配位数超出范围或重复时触发
+    #[msg("Invalid prize tiers")]
+    InvalidPrizeTiers,
+    
+    /// 无效的批量生成数量
+    /// 当批量生成的数量为0或超过MAX_BATCH_COUNT时触发
+    #[msg("Invalid batch count")]
+    InvalidBatchCount,
+    
+    /// 无效的指定数字价格倍数
+    /// 当价格倍数低于1倍或超过MAX_SPECIFIC_NUMBER_PREMIUM_BPS时触发
+    #[msg("Invalid specific number premium")]
+    InvalidSpecificNumberPremium,
+    
+    /// 无效的转移接收方
+    /// 当把数字转移给自己或默认地址时触发
+    #[msg("Invalid transfer recipient")]
+    InvalidTransferRecipient,
+    
+    /// 数字不在用户账户中
+    /// 当用户账户的数字列表中找不到要代币化的数字时触发
+    #[msg("Number not found in user account")]
+    NumberNotInUserAccount,
+    
+    /// 无效的代币价格
+    /// 当设置支付代币时代币价格为0时触发
+    #[msg("Invalid token price")]
+    InvalidTokenPrice,
+    
+    /// 无效的支付代币
+    /// 当提供的mint或代币账户与数字池的支付代币不一致时触发
+    #[msg("Invalid payment mint")]
+    InvalidPaymentMint,
+    
+    /// 缺少代币支付账户
+    /// 当数字池以代币支付但未提供mint、代币账户或代币程序时触发
+    #[msg("Token payment accounts missing")]
+    TokenPaymentAccountsMissing,
+    
+    /// 不支持代币支付
+    /// 当数字池以代币支付时使用提交-揭示或预言机模式生成，或提供推荐人时触发
+    #[msg("Token payment not supported")]
+    TokenPaymentNotSupported,
+    
+    /// 不可转让的支付代币
+    /// 当支付代币的mint带有NonTransferable扩展时触发
+    #[msg("Payment mint is non-transferable")]
+    NonTransferableMint,
+    
+    /// 带有永久委托人的支付代币
+    /// 当支付代币的mint带有PermanentDelegate扩展时触发，永久委托人可以转走财库中的代币
+    #[msg("Payment mint has a permanent delegate")]
+    PermanentDelegateMint,
+    
+    /// 带有转账钩子的支付代币
+    /// 当支付代币的mint带有TransferHook扩展时触发
+    #[msg("Payment mint has a transfer hook")]
+    TransferHookMint,
+    
+    /// 转账手续费计算溢出
+    /// 当加上转账手续费后的支付数量超出u64范围时触发
+    #[msg("Transfer fee overflow")]
+    TransferFeeOverflow,
+    
+    /// 价格超过最高价格
+    /// 当按价格曲线计算的价格高于用户指定的max_price时触发
+    #[msg("Price exceeds max price")]
+    PriceExceedsMax,
+    
+    /// 价格计算溢出
+    /// 当按价格曲线计算的价格超出u64范围时触发
+    #[msg("Price overflow")]
+    PriceOverflow,
+    
+    /// 无效的价格曲线
+    /// 当指数曲线涨幅超过MAX_EXPONENTIAL_SLOPE_BPS或范围内最后一个数字的价格溢出时触发
+    #[msg("Invalid price curve")]
+    InvalidPriceCurve,
+    
+    /// 自我推荐
+    /// 当用户把自己的推荐奖励账户作为推荐人时触发
+    #[msg("Self-referral is not allowed")]
+    SelfReferral,
+    
+    /// 无效的推荐比例
+    /// 当推荐比例超过MAX_REFERRAL_BPS时触发
+    #[msg("Invalid referral share")]
+    InvalidReferralShare,
+    
+    /// 没有可领取的推荐奖励
+    /// 当推荐奖励账户中没有待领取的奖励时触发
+    #[msg("No referral rewards to claim")]
+    NoReferralRewards,
+    
+    /// 无效的分账表
+    /// 当接收方超过MAX_FEE_RECIPIENTS、地址为默认值或重复、份额为0或份额之和不等于10,000时触发
+    #[msg("Invalid fee split")]
+    InvalidFeeSplit,
+    
+    /// 存在尚未支付的分账
+    /// 当还有已分得但尚未支付的lamports或代币时修改或取消分账表触发
+    #[msg("Undistributed fees remain")]
+    UndistributedFees,
+    
+    /// 未设置分账表
+    /// 当没有设置分账表时调用distribute或distribute_tokens触发
+    #[msg("Fee split not set")]
+    FeeSplitNotSet,
+    
+    /// 分账表生效中
+    /// 当设置了分账表时数字池管理员提取lamports或代币资金触发
+    #[msg("Fee split is active")]
+    FeeSplitActive,
+    
+    /// 没有可分配的资金
+    /// 当财库（或财库代币账户）中没有可分配的资金且没有向任何接收方支付时触发
+    #[msg("Nothing to distribute")]
+    NothingToDistribute,
+    
+    /// 尚未请求开奖
+    /// 当请求开奖之前尝试开奖时触发
+    #[msg("Draw has not been requested")]
+    DrawNotRequested,
+    
+    /// 已请求开奖
+    /// 当同一轮次重复请求开奖时触发
+    #[msg("Draw already requested")]
+    DrawAlreadyRequested,
+    
+    /// 尚未到达开奖slot
+    /// 当开奖使用的区块哈希尚未产生时尝试开奖触发
+    #[msg("Draw slot not reached")]
+    DrawSlotNotReached,
+    
+    /// 数字不能领奖
+    /// 当开奖之后才发放的数字尝试领取该轮奖金时触发
+    #[msg("Number was issued after the draw")]
+    NumberNotEligible,
+    
+    /// 奖金超过预留总额
+    /// 当领取的奖金大于数字池预留的奖金总额时触发，说明奖金记账异常
+    #[msg("Prize exceeds reserved prizes")]
+    PrizeExceedsReserved,
+    
+    /// 接收方无效
+    /// 当设置的固定接收方为数字池或其财库，或提取时的接收方与数字池设置的固定接收方不一致时触发
+    #[msg("Invalid withdrawal recipient")]
+    InvalidWithdrawalRecipient,
 }
\ No newline at end of file
@@ -0,0 +1,534 @@
+use anchor_lang::prelude::*;
+use crate::state::{FeeRecipient, LuckSnakeConfig, Pool, PriceCurve, PrizeTier};
+
+/// 配置初始化事件
+/// 程序初始化时触发
+#[event]
+pub struct ConfigInitialized {
+    /// 管理员地址
+    pub authority: Pubkey,
+}
+
+/// 配置迁移事件
+/// 调用migrate_config时触发，账户已是最新版本时修改前后的大小相同
+#[event]
+pub struct ConfigMigrated {
+    /// 执行迁移的管理员地址
+    pub authority: Pubkey,
+    /// 迁移前的账户大小（单位：字节）
+    pub old_len: u32,
+    /// 迁移后的账户大小（单位：字节）
+    pub new_len: u32,
+}
+
+/// 数字池创建事件
+#[event]
+pub struct PoolCreated {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 数字池编号
+    pub pool_id: u64,
+    /// 数字池管理员地址
+    pub authority: Pubkey,
+    /// 财库PDA地址
+    pub treasury: Pubkey,
+    /// 生成价格（单位：lamports）
+    pub generation_price: u64,
+    /// 可生成数字的最小值（包含）
+    pub range_min: u32,
+    /// 可生成数字的最大值（包含）
+    pub range_max: u32,
+}
+
+/// 用户账户初始化事件
+#[event]
+pub struct UserInitialized {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 用户账户PDA地址
+    pub user_account: Pubkey,
+}
+
+/// 用户账户关闭事件
+/// 用户不再持有数字、关闭账户取回租金时触发
+#[event]
+pub struct UserAccountClosed {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 已关闭的用户账户PDA地址
+    pub user_account: Pubkey,
+}
+
+/// 数字生成事件
+/// 直接生成、提交-揭示和预言机模式发放数字时都会触发
+#[event]
+pub struct NumberGenerated {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 生成的数字
+    pub number: u32,
+    /// 数字所有者
+    pub owner: Pubkey,
+    /// 发放数字时的slot
+    pub slot: u64,
+    /// 用户为该数字支付的费用（单位：lamports，数字池以代币支付时为代币的最小单位）
+    pub price_paid: u64,
+    /// 发放后本池已生成的数字总数
+    pub total_generated_numbers: u32,
+    /// 发放后该用户持有的数字数量
+    pub user_number_count: u32,
+}
+
+/// 提交生成请求事件
+/// 提交-揭示模式下用户提交哈希承诺时触发
+#[event]
+pub struct GenerationCommitted {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 暂存的生成费用（单位：lamports）
+    pub amount: u64,
+    /// 提交时的slot
+    pub commit_slot: u64,
+    /// 揭示时使用的区块哈希所在的slot
+    pub reveal_slot: u64,
+}
+
+/// 过期生成请求退款事件
+#[event]
+pub struct GenerationRefunded {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 退还给用户的费用（单位：lamports，不含请求账户的租金）
+    pub refunded: u64,
+    /// 不退还、转入财库的费用（单位：lamports）
+    pub forfeited: u64,
+}
+
+/// 预言机随机数请求事件
+#[event]
+pub struct VrfRequested {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 暂存的生成费用（单位：lamports）
+    pub amount: u64,
+    /// 发起请求时的slot
+    pub request_slot: u64,
+}
+
+/// 预言机回填随机数事件
+#[event]
+pub struct VrfFulfilled {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 回填时的slot
+    pub slot: u64,
+}
+
+/// 预言机请求取消事件
+/// 预言机超时未回填、用户取消请求取回费用时触发
+#[event]
+pub struct VrfCancelled {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 用户钱包地址
+    pub user: Pubkey,
+    /// 退还给用户的费用（单位：lamports，不含请求账户的租金）
+    pub amount: u64,
+}
+
+/// 资金提取事件
+#[event]
+pub struct FundsWithdrawn {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 执行提取的数字池管理员地址
+    pub authority: Pubkey,
+    /// 接收方地址
+    pub recipient: Pubkey,
+    /// 提取金额（单位：lamports）
+    pub amount: u64,
+    /// 提取后财库余额（单位：lamports）
+    pub treasury_balance: u64,
+    /// 累计提取次数
+    pub total_withdrawals: u64,
+}
+
+/// 固定接收方变更事件
+/// 程序管理员设置或轮换数字池提取资金的接收方时触发
+#[event]
+pub struct WithdrawalRecipientUpdated {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 执行修改的程序管理员地址
+    pub authority: Pubkey,
+    /// 修改前的接收方，默认值表示未设置
+    pub old_recipient: Pubkey,
+    /// 修改后的接收方，默认值表示取消限制
+    pub new_recipient: Pubkey,
+}
+
+/// 数字池更新事件
+/// 数字池管理员修改参数时触发，同时记录修改前后的值
+#[event]
+pub struct PoolUpdated {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 执行修改的数字池管理员地址
+    pub authority: Pubkey,
+    /// 修改前的生成价格（单位：lamports）
+    pub old_generation_price: u64,
+    /// 修改后的生成价格（单位：lamports）
+    pub new_generation_price: u64,
+    /// 修改前的奖级表
+    pub old_prize_tiers: [PrizeTier; Pool::MAX_PRIZE_TIERS],
+    /// 修改后的奖级表
+    pub new_prize_tiers: [PrizeTier; Pool::MAX_PRIZE_TIERS],
+    /// 修改前的指定数字价格倍数（单位：基点）
+    pub old_specific_number_premium_bps: u32,
+    /// 修改后的指定数字价格倍数（单位：基点）
+    pub new_specific_number_premium_bps: u32,
+}
+
+/// 管理员转移提议事件
+#[event]
+pub struct AuthorityTransferProposed {
+    /// 当前管理员地址
+    pub authority: Pubkey,
+    /// 被提议的新管理员地址
+    pub pending_authority: Pubkey,
+}
+
+/// 管理员转移完成事件
+#[event]
+pub struct AuthorityTransferAccepted {
+    /// 原管理员地址
+    pub old_authority: Pubkey,
+    /// 新管理员地址
+    pub new_authority: Pubkey,
+}
+
+/// 管理员转移取消事件
+#[event]
+pub struct AuthorityTransferCancelled {
+    /// 当前管理员地址
+    pub authority: Pubkey,
+    /// 被取消的待接受管理员地址
+    pub pending_authority: Pubkey,
+}
+
+/// 暂停生成事件
+#[event]
+pub struct GenerationPaused {
+    /// 执行暂停的地址（管理员或守护者）
+    pub paused_by: Pubkey,
+}
+
+/// 恢复生成事件
+#[event]
+pub struct GenerationUnpaused {
+    /// 执行恢复的管理员地址
+    pub authority: Pubkey,
+}
+
+/// 守护者变更事件
+#[event]
+pub struct GuardianUpdated {
+    /// 执行修改的管理员地址
+    pub authority: Pubkey,
+    /// 修改前的守护者地址
+    pub old_guardian: Pubkey,
+    /// 修改后的守护者地址
+    pub new_guardian: Pubkey,
+}
+
+/// 预言机设置变更事件
+#[event]
+pub struct OracleUpdated {
+    /// 执行修改的管理员地址
+    pub authority: Pubkey,
+    /// 修改前的预言机程序地址
+    pub old_oracle_program: Pubkey,
+    /// 修改后的预言机程序地址，默认地址表示关闭预言机模式
+    pub new_oracle_program: Pubkey,
+    /// 修改前的预言机授权地址
+    pub old_oracle_authority: Pubkey,
+    /// 修改后的预言机授权地址
+    pub new_oracle_authority: Pubkey,
+}
+
+/// 轮次开启事件
+#[event]
+pub struct RoundOpened {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 轮次账户地址
+    pub round: Pubkey,
+    /// 轮次编号
+    pub round_id: u64,
+    /// 截止时间（Unix时间戳）
+    pub close_time: i64,
+}
+
+/// 请求开奖事件
+/// 截止之后首次请求开奖或开奖请求过期后重新请求时触发
+#[event]
+pub struct DrawRequested {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 轮次账户地址
+    pub round: Pubkey,
+    /// 轮次编号
+    pub round_id: u64,
+    /// 开奖使用的区块哈希所在的slot
+    pub draw_slot: u64,
+}
+
+/// 开奖事件
+#[event]
+pub struct RoundDrawn {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 轮次账户地址
+    pub round: Pubkey,
+    /// 轮次编号
+    pub round_id: u64,
+    /// 中奖数字
+    pub winning_number: u32,
+    /// 本轮奖池金额（单位：lamports）
+    pub pot: u64,
+    /// 各奖级命中的数字个数
+    pub tier_winners: [u32; Pool::MAX_PRIZE_TIERS],
+    /// 各奖级每个命中数字可领取的奖金（单位：lamports）
+    pub tier_prizes: [u64; Pool::MAX_PRIZE_TIERS],
+    /// 没有命中数字的奖级份额，滚入下一轮奖池（单位：lamports）
+    pub rolled_over: u64,
+}
+
+/// 奖金领取事件
+#[event]
+pub struct PrizeClaimed {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 轮次账户地址
+    pub round: Pubkey,
+    /// 中奖者地址
+    pub winner: Pubkey,
+    /// 领奖的数字
+    pub number: u32,
+    /// 命中奖级的匹配位数
+    pub match_digits: u8,
+    /// 领取的奖金（单位：lamports）
+    pub amount: u64,
+}
+
+/// 数字登记事件
+/// 随机生成时未创建记录的数字由所有者登记NumberRecord时触发
+#[event]
+pub struct NumberRegistered {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 登记的数字
+    pub number: u32,
+    /// 数字所有者地址
+    pub owner: Pubkey,
+    /// 数字记录PDA地址
+    pub number_record: Pubkey,
+}
+
+/// 数字转移事件
+#[event]
+pub struct NumberTransferred {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 转移的数字
+    pub number: u32,
+    /// 原所有者地址
+    pub from: Pubkey,
+    /// 新所有者地址
+    pub to: Pubkey,
+}
+
+/// 数字代币化事件
+#[event]
+pub struct NumberTokenized {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 代币化的数字
+    pub number: u32,
+    /// 代币的mint地址
+    pub mint: Pubkey,
+    /// 接收代币的钱包地址
+    pub owner: Pubkey,
+}
+
+/// 支付代币变更事件
+/// 数字池管理员设置或清除支付代币时触发
+#[event]
+pub struct PaymentMintUpdated {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 修改前的支付代币mint地址，默认值表示以lamports支付
+    pub old_payment_mint: Pubkey,
+    /// 修改后的支付代币mint地址，默认值表示以lamports支付
+    pub new_payment_mint: Pubkey,
+    /// 代币价格（单位：代币的最小单位），清除支付代币时为0
+    pub token_price: u64,
+}
+
+/// 代币资金提取事件
+#[event]
+pub struct TokenFundsWithdrawn {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 执行提取的数字池管理员地址
+    pub authority: Pubkey,
+    /// 代币的mint地址
+    pub mint: Pubkey,
+    /// 接收方代币账户地址
+    pub recipient: Pubkey,
+    /// 提取金额（单位：代币的最小单位）
+    pub amount: u64,
+    /// 提取后财库代币账户余额
+    pub treasury_balance: u64,
+}
+
+/// 价格曲线变更事件
+#[event]
+pub struct PriceCurveUpdated {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 修改前的价格曲线
+    pub old_price_curve: PriceCurve,
+    /// 修改前的曲线斜率
+    pub old_price_curve_slope: u64,
+    /// 修改后的价格曲线
+    pub new_price_curve: PriceCurve,
+    /// 修改后的曲线斜率
+    pub new_price_curve_slope: u64,
+}
+
+/// 推荐人注册事件
+#[event]
+pub struct ReferralInitialized {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 推荐人钱包地址
+    pub referrer: Pubkey,
+    /// 推荐奖励账户PDA地址
+    pub referral_rewards: Pubkey,
+}
+
+/// 推荐比例变更事件
+#[event]
+pub struct ReferralBpsUpdated {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 修改前的推荐比例（单位：基点）
+    pub old_referral_bps: u16,
+    /// 修改后的推荐比例（单位：基点）
+    pub new_referral_bps: u16,
+}
+
+/// 推荐奖励累积事件
+/// 被推荐用户支付生成费用时触发
+#[event]
+pub struct ReferralRewardAccrued {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 推荐人地址
+    pub referrer: Pubkey,
+    /// 被推荐的用户地址
+    pub user: Pubkey,
+    /// 本次分给推荐人的金额（单位：lamports）
+    pub amount: u64,
+    /// 本次转入财库的金额（单位：lamports）
+    pub treasury_amount: u64,
+    /// 推荐人尚未领取的奖励总额（单位：lamports）
+    pub accrued: u64,
+}
+
+/// 推荐奖励领取事件
+#[event]
+pub struct ReferralRewardsClaimed {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 推荐人地址
+    pub referrer: Pubkey,
+    /// 领取金额（单位：lamports）
+    pub amount: u64,
+}
+
+/// 分账表变更事件
+#[event]
+pub struct FeeSplitUpdated {
+    /// 执行修改的管理员地址
+    pub authority: Pubkey,
+    /// 修改前的分账表
+    pub old_fee_recipients: [FeeRecipient; LuckSnakeConfig::MAX_FEE_RECIPIENTS],
+    /// 修改后的分账表
+    pub new_fee_recipients: [FeeRecipient; LuckSnakeConfig::MAX_FEE_RECIPIENTS],
+}
+
+/// 分账事件
+/// 每次调用distribute时触发
+#[event]
+pub struct FeesDistributed {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 本次从财库转出并按份额分给各接收方的金额（单位：lamports）
+    pub amount: u64,
+    /// 本次向接收方支付的总金额（单位：lamports）
+    pub paid: u64,
+    /// 分配后各接收方尚未支付的金额，与分账表按下标对应
+    pub fee_accrued: [u64; LuckSnakeConfig::MAX_FEE_RECIPIENTS],
+}
+
+/// 分账支付事件
+#[event]
+pub struct FeeRecipientPaid {
+    /// 接收方钱包地址
+    pub recipient: Pubkey,
+    /// 支付金额（单位：lamports）
+    pub amount: u64,
+}
+
+/// 代币分账事件
+/// 每次调用distribute_tokens时触发
+#[event]
+pub struct TokenFeesDistributed {
+    /// 数字池地址
+    pub pool: Pubkey,
+    /// 代币的mint地址
+    pub mint: Pubkey,
+    /// 本次按份额分给各接收方的代币数量
+    pub amount: u64,
+    /// 本次向接收方支付的代币总数
+    pub paid: u64,
+    /// 分配后各接收方尚未支付的代币数量，与分账表按下标对应
+    pub accrued: [u64; LuckSnakeConfig::MAX_FEE_RECIPIENTS],
+}
+
+/// 代币分账支付事件
+#[event]
+pub struct TokenFeeRecipientPaid {
+    /// 接收方钱包地址
+    pub recipient: Pubkey,
+    /// 代币的mint地址
+    pub mint: Pubkey,
+    /// 接收方代币账户地址
+    pub recipient_token_account: Pubkey,
+    /// 从财库代币账户转出的数量，mint带有转账手续费时接收方实收更少
+    pub amount: u64,
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::AuthorityTransferAccepted;
+
+/// 接受管理员转移指令的账户结构
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    /// 程序配置账户
+    /// 只有待接受的新管理员可以接受
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = config.pending_authority != Pubkey::default() @ LuckSnakeError::NoPendingAuthority,
+        constraint = config.pending_authority == new_authority.key() @ LuckSnakeError::Unauthorized
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 新管理员账户
+    /// 需要签名，证明新管理员持有对应私钥
+    pub new_authority: Signer<'info>,
+}
+
+/// 接受管理员转移处理函数
+pub fn handler(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let old_authority = config.authority;
+    
+    config.authority = ctx.accounts.new_authority.key();
+    config.pending_authority = Pubkey::default();
+    
+    emit!(AuthorityTransferAccepted {
+        old_authority,
+        new_authority: config.authority,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::AuthorityTransferCancelled;
+
+/// 取消管理员转移指令的账户结构
+#[derive(Accounts)]
+pub struct CancelAuthorityTransfer<'info> {
+    /// 程序配置账户
+    /// 只有当前管理员可以取消
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 当前管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 取消管理员转移处理函数
+pub fn handler(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    
+    require!(
+        config.pending_authority != Pubkey::default(),
+        LuckSnakeError::NoPendingAuthority
+    );
+    
+    let pending_authority = config.pending_authority;
+    config.pending_authority = Pubkey::default();
+    
+    emit!(AuthorityTransferCancelled {
+        authority: config.authority,
+        pending_authority,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::VrfCancelled;
+
+/// 取消预言机请求指令的账户结构
+#[derive(Accounts)]
+pub struct CancelVrfGeneration<'info> {
+    /// 程序配置账户
+    /// 检查全局暂停状态
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    /// 检查请求是否还能完成
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 随机数请求账户
+    /// 关闭后暂存的费用和租金全部退还给用户
+    #[account(
+        mut,
+        seeds = [VrfRequest::SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = vrf_request.bump,
+        has_one = user,
+        close = user
+    )]
+    pub vrf_request: Account<'info, VrfRequest>,
+    
+    /// 用户钱包账户
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+/// 取消预言机请求处理函数
+/// 当前无法完成生成（生成已暂停、数字已全部发放或轮次已截止）时随时全额退款，
+/// 否则只有预言机超时未回填的请求才能取消，已回填的请求应通过complete_vrf_generation完成
+pub fn handler(ctx: Context<CancelVrfGeneration>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let pool = &ctx.accounts.pool;
+    let vrf_request = &ctx.accounts.vrf_request;
+    let clock = Clock::get()?;
+    
+    let completion_blocked = config.paused
+        || pool.total_generated_numbers >= pool.number_count()
+        || !pool.is_accepting_numbers(clock.unix_timestamp);
+    if !completion_blocked {
+        require!(
+            !vrf_request.fulfilled,
+            LuckSnakeError::VrfRequestAlreadyFulfilled
+        );
+        require!(
+            vrf_request.is_timed_out(clock.slot),
+            LuckSnakeError::VrfRequestNotTimedOut
+        );
+    }
+    
+    emit!(VrfCancelled {
+        pool: vrf_request.pool,
+        user: vrf_request.user,
+        amount: vrf_request.amount,
+    });
+    
+    Ok(())
+}
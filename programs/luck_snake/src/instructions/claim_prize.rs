@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::PrizeClaimed;
+
+/// 领取奖金指令的账户结构
+#[derive(Accounts)]
+#[instruction(number: u32)]
+pub struct ClaimPrize<'info> {
+    /// 数字池账户
+    /// 领取后释放预留的奖金
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = treasury
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 轮次账户
+    #[account(
+        mut,
+        seeds = [Round::SEED, pool.key().as_ref(), &round.round_id.to_le_bytes()],
+        bump = round.bump,
+        has_one = pool,
+        constraint = round.drawn @ LuckSnakeError::RoundNotDrawn
+    )]
+    pub round: Account<'info, Round>,
+    
+    /// 领奖者的用户账户
+    /// 可选，数字在其中时即为所有者，未代币化的数字不需要数字记录
+    #[account(
+        seeds = [UserAccount::SEED, pool.key().as_ref(), winner.key().as_ref()],
+        bump = winner_account.bump
+    )]
+    pub winner_account: Option<Account<'info, UserAccount>>,
+    
+    /// 领奖数字的记录账户
+    /// 可选，已代币化的数字通过记录和持有代币的代币账户验证所有者
+    #[account(
+        seeds = [NumberRecord::SEED, pool.key().as_ref(), &number.to_le_bytes()],
+        bump = number_record.bump
+    )]
+    pub number_record: Option<Account<'info, NumberRecord>>,
+    
+    /// 领奖者持有数字代币的代币账户
+    /// 仅在数字已代币化时需要提供
+    pub holder_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// 财库账户
+    /// 奖金从这里转出
+    #[account(
+        mut,
+        seeds = [Treasury::SEED, pool.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    /// 领奖数字的所有者钱包账户
+    #[account(mut)]
+    pub winner: Signer<'info>,
+}
+
+/// 领取奖金处理函数
+/// 按数字命中的最高奖级，将该奖级每个命中数字的奖金从财库转给数字的所有者
+/// 所有者由领奖者的用户账户证明，已代币化的数字由数字记录和持有代币的代币账户证明
+/// 持有多个命中数字时需为每个数字分别领取，开奖之后才发放的数字不能领取
+pub fn handler(ctx: Context<ClaimPrize>, number: u32) -> Result<()> {
+    let winner = ctx.accounts.winner.key();
+    let held_in_account = ctx
+        .accounts
+        .winner_account
+        .as_ref()
+        .is_some_and(|winner_account| winner_account.numbers.contains(&number));
+    let held_by_record = ctx
+        .accounts
+        .number_record
+        .as_ref()
+        .is_some_and(|number_record| {
+            number_record.is_held_by(&winner, ctx.accounts.holder_token_account.as_deref())
+        });
+    require!(held_in_account || held_by_record, LuckSnakeError::Unauthorized);
+    
+    let round = &mut ctx.accounts.round;
+    let index = number - ctx.accounts.pool.range_min;
+    
+    // 开奖时的奖金按当时已发放的数字个数平分，之后发放的命中数字不在其中
+    require!(round.is_eligible(index), LuckSnakeError::NumberNotEligible);
+    
+    let tier = round
+        .winning_tier(number)
+        .filter(|tier| round.tier_prizes[*tier] > 0)
+        .ok_or(LuckSnakeError::NoPrize)?;
+    require!(!round.is_claimed(index), LuckSnakeError::PrizeAlreadyClaimed);
+    
+    let prize = round.tier_prizes[tier];
+    round.mark_claimed(index);
+    ctx.accounts.pool.reserved_prizes = ctx
+        .accounts
+        .pool
+        .reserved_prizes
+        .checked_sub(prize)
+        .ok_or(LuckSnakeError::PrizeExceedsReserved)?;
+    
+    // 财库由本程序拥有，直接修改lamports完成转账
+    ctx.accounts.treasury.sub_lamports(prize)?;
+    ctx.accounts.winner.add_lamports(prize)?;
+    
+    emit!(PrizeClaimed {
+        pool: ctx.accounts.pool.key(),
+        round: round.key(),
+        winner: ctx.accounts.winner.key(),
+        number,
+        match_digits: round.prize_tiers[tier].match_digits,
+        amount: prize,
+    });
+    
+    Ok(())
+}
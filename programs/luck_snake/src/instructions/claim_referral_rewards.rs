@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::ReferralRewardsClaimed;
+
+/// 领取推荐奖励指令的账户结构
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    /// 推荐奖励账户
+    /// 只有推荐人本人可以领取
+    #[account(
+        mut,
+        seeds = [ReferralRewards::SEED, referral_rewards.pool.as_ref(), referrer.key().as_ref()],
+        bump = referral_rewards.bump,
+        has_one = referrer @ LuckSnakeError::Unauthorized
+    )]
+    pub referral_rewards: Account<'info, ReferralRewards>,
+    
+    /// 推荐人钱包账户
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+}
+
+/// 领取推荐奖励处理函数
+/// 将累计的全部推荐奖励转给推荐人，账户租金保留在推荐奖励账户中
+pub fn handler(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+    let referral_rewards = &mut ctx.accounts.referral_rewards;
+    let amount = referral_rewards.accrued;
+    require!(amount > 0, LuckSnakeError::NoReferralRewards);
+    
+    referral_rewards.accrued = 0;
+    
+    // 推荐奖励账户由本程序拥有，直接修改lamports完成转账
+    referral_rewards.sub_lamports(amount)?;
+    ctx.accounts.referrer.add_lamports(amount)?;
+    
+    emit!(ReferralRewardsClaimed {
+        pool: referral_rewards.pool,
+        referrer: referral_rewards.referrer,
+        amount,
+    });
+    
+    Ok(())
+}
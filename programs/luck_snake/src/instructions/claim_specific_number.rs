@@ -0,0 +1,178 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::NumberGenerated;
+use super::generate_number::{collect_payment, create_number_record, TokenPaymentAccounts};
+
+/// 购买指定数字指令的账户结构
+#[derive(Accounts)]
+#[instruction(number: u32)]
+pub struct ClaimSpecificNumber<'info> {
+    /// 程序配置账户
+    /// 检查全局暂停状态
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = !config.paused @ LuckSnakeError::GenerationPaused
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 已发放数字位图账户
+    #[account(
+        mut,
+        seeds = [NumberPool::SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub number_pool: AccountLoader<'info, NumberPool>,
+    
+    /// 指定数字的记录账户
+    /// CHECK: 通过seeds约束验证地址，由create_number_record创建并检查是否已被占用
+    #[account(
+        mut,
+        seeds = [NumberRecord::SEED, pool.key().as_ref(), &number.to_le_bytes()],
+        bump
+    )]
+    pub number_record: UncheckedAccount<'info>,
+    
+    /// 用户账户
+    /// 需先通过init_user创建，扩容一个数字的空间
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+        has_one = user,
+        realloc = user_account.space_after_adding_number(),
+        realloc::payer = user,
+        realloc::zero = false
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    
+    /// 用户钱包账户
+    /// 需要签名，支付指定数字的价格
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// 财库账户
+    #[account(
+        mut,
+        seeds = [Treasury::SEED, pool.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+    
+    /// 支付代币的mint账户
+    /// 以下代币支付账户仅在数字池以代币支付时需要提供
+    #[account(address = pool.payment_mint @ LuckSnakeError::InvalidPaymentMint)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// 用户支付费用的代币账户
+    #[account(
+        mut,
+        constraint = user_token_account.mint == pool.payment_mint @ LuckSnakeError::InvalidPaymentMint
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// 财库代币账户
+    #[account(
+        mut,
+        seeds = [Pool::TREASURY_TOKEN_SEED, pool.key().as_ref(), pool.payment_mint.as_ref()],
+        bump
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// SPL Token或Token-2022程序
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    /// 推荐人的推荐奖励账户
+    /// 可选，提供时按数字池的推荐比例把一部分费用转入该账户，推荐人不能是用户自己
+    #[account(
+        mut,
+        seeds = [ReferralRewards::SEED, pool.key().as_ref(), referral_rewards.referrer.as_ref()],
+        bump = referral_rewards.bump,
+        constraint = referral_rewards.referrer != user.key() @ LuckSnakeError::SelfReferral
+    )]
+    pub referral_rewards: Option<Account<'info, ReferralRewards>>,
+}
+
+/// 购买指定数字处理函数
+/// 与随机生成一样创建数字记录、标记位图并更新计数，价格为生成价格乘以指定数字价格倍数
+pub fn handler(ctx: Context<ClaimSpecificNumber>, number: u32, max_price: Option<u64>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+    
+    require!(
+        (pool.range_min..=pool.range_max).contains(&number),
+        LuckSnakeError::InvalidNumberRange
+    );
+    require!(
+        pool.is_accepting_numbers(clock.unix_timestamp),
+        LuckSnakeError::RoundClosed
+    );
+    
+    let index = number - pool.range_min;
+    let mut number_pool = ctx.accounts.number_pool.load_mut()?;
+    require!(
+        !number_pool.is_issued(index),
+        LuckSnakeError::NumberAlreadyGenerated
+    );
+    
+    // 收取指定数字的价格
+    let price = pool.specific_number_price().ok_or(LuckSnakeError::PriceOverflow)?;
+    collect_payment(
+        pool,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.treasury.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        TokenPaymentAccounts {
+            payment_mint: ctx.accounts.payment_mint.as_ref(),
+            user_token_account: ctx.accounts.user_token_account.as_ref(),
+            treasury_token_account: ctx.accounts.treasury_token_account.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+        },
+        ctx.accounts.referral_rewards.as_mut(),
+        price,
+        max_price,
+    )?;
+    
+    // 创建数字记录并在位图中标记，与随机生成相同
+    create_number_record(
+        &ctx.accounts.number_record.to_account_info(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        pool.key(),
+        number,
+        ctx.accounts.user.key(),
+        ctx.bumps.number_record,
+    )?;
+    number_pool.mark_issued(index);
+    
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.numbers.push(number);
+    
+    // 更新数字池计数器
+    pool.total_generated_numbers += 1;
+    
+    emit!(NumberGenerated {
+        pool: pool.key(),
+        number,
+        owner: ctx.accounts.user.key(),
+        slot: clock.slot,
+        price_paid: price,
+        total_generated_numbers: pool.total_generated_numbers,
+        user_number_count: user_account.numbers.len() as u32,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::PaymentMintUpdated;
+
+/// 清除支付代币指令的账户结构
+#[derive(Accounts)]
+pub struct ClearPaymentMint<'info> {
+    /// 数字池账户
+    /// 只有数字池管理员可以清除支付代币
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 数字池管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 清除支付代币处理函数
+/// 之后生成数字恢复以lamports支付，财库代币账户中的余额仍可通过withdraw_token_funds提取
+pub fn handler(ctx: Context<ClearPaymentMint>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let old_payment_mint = pool.payment_mint;
+    pool.payment_mint = Pubkey::default();
+    pool.token_price = 0;
+    
+    emit!(PaymentMintUpdated {
+        pool: pool.key(),
+        old_payment_mint,
+        new_payment_mint: pool.payment_mint,
+        token_price: 0,
+    });
+    
+    Ok(())
+}
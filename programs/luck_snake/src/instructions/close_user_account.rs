@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::UserAccountClosed;
+
+/// 关闭用户账户的账户结构
+#[derive(Accounts)]
+pub struct CloseUserAccount<'info> {
+    /// 用户账户
+    /// 关闭后租金全部退还给用户钱包
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED, user_account.pool.as_ref(), user.key().as_ref()], // PDA种子包含数字池和用户地址
+        bump = user_account.bump,                         // 使用存储的bump值
+        has_one = user,                                   // 验证账户属于该用户
+        close = user                                      // 关闭账户，租金退还给用户
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    
+    /// 用户钱包账户
+    /// 需要签名，接收退还的租金
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+/// 关闭用户账户处理函数
+/// 只有在用户已经不再持有任何数字时才能关闭
+pub fn handler(ctx: Context<CloseUserAccount>) -> Result<()> {
+    require!(
+        ctx.accounts.user_account.numbers.is_empty(),
+        LuckSnakeError::UserAccountNotEmpty
+    );
+    
+    emit!(UserAccountClosed {
+        pool: ctx.accounts.user_account.pool,
+        user: ctx.accounts.user.key(),
+        user_account: ctx.accounts.user_account.key(),
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::GenerationCommitted;
+use super::generate_number::check_max_price;
+
+/// 提交生成请求指令的账户结构
+#[derive(Accounts)]
+pub struct CommitGeneration<'info> {
+    /// 程序配置账户
+    /// 检查全局暂停状态
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = !config.paused @ LuckSnakeError::GenerationPaused
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    /// 提供当前的生成价格
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 待揭示请求账户
+    /// 每个用户在每个数字池中同时只能有一个未完成的请求
+    #[account(
+        init,
+        payer = user,
+        space = PendingGeneration::LEN,
+        seeds = [PendingGeneration::SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub pending: Account<'info, PendingGeneration>,
+    
+    /// 用户钱包账户
+    /// 需要签名，支付生成费用和请求账户的租金
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 提交生成请求处理函数
+/// 记录承诺并将生成费用暂存到请求账户中
+pub fn handler(
+    ctx: Context<CommitGeneration>,
+    commitment: [u8; 32],
+    max_price: Option<u64>,
+) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    
+    // 检查是否还有剩余数字可生成，避免用户为无法完成的请求付款
+    require!(
+        pool.total_generated_numbers < pool.number_count(),
+        LuckSnakeError::AllNumbersGenerated
+    );
+    
+    // 轮次截止之后不能揭示，提交时本轮必须还能在整个揭示窗口内发放数字
+    require!(
+        pool.is_accepting_numbers(Clock::get()?.unix_timestamp + PendingGeneration::ROUND_CLOSE_BUFFER_SECONDS),
+        LuckSnakeError::RoundClosed
+    );
+    
+    // 费用以lamports暂存在请求账户中，代币支付的数字池只能直接生成
+    require!(!pool.pays_with_token(), LuckSnakeError::TokenPaymentNotSupported);
+    
+    let price = pool.current_price().ok_or(LuckSnakeError::PriceOverflow)?;
+    check_max_price(price, max_price)?;
+    
+    // 将生成费用转入请求账户暂存，揭示时再转入财库
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.pending.to_account_info(),
+            },
+        ),
+        price,
+    )?;
+    
+    let pending = &mut ctx.accounts.pending;
+    pending.pool = pool.key();
+    pending.user = ctx.accounts.user.key();
+    pending.commitment = commitment;
+    pending.amount = price;
+    pending.commit_slot = Clock::get()?.slot;
+    pending.bump = ctx.bumps.pending;
+    
+    emit!(GenerationCommitted {
+        pool: pending.pool,
+        user: pending.user,
+        amount: pending.amount,
+        commit_slot: pending.commit_slot,
+        reveal_slot: pending.reveal_slot(),
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::randomness;
+use crate::events::NumberGenerated;
+use super::generate_number::issue_number;
+
+/// 完成预言机生成指令的账户结构
+#[derive(Accounts)]
+pub struct CompleteVrfGeneration<'info> {
+    /// 程序配置账户
+    /// 检查全局暂停状态
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = !config.paused @ LuckSnakeError::GenerationPaused
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 已发放数字位图账户
+    #[account(
+        mut,
+        seeds = [NumberPool::SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub number_pool: AccountLoader<'info, NumberPool>,
+    
+    /// 随机数请求账户
+    /// 完成后关闭，租金退还给用户
+    #[account(
+        mut,
+        seeds = [VrfRequest::SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = vrf_request.bump,
+        has_one = user,
+        close = user
+    )]
+    pub vrf_request: Account<'info, VrfRequest>,
+    
+    /// 用户账户
+    /// 需先通过init_user创建，扩容一个数字的空间
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+        has_one = user,
+        realloc = user_account.space_after_adding_number(),
+        realloc::payer = user,
+        realloc::zero = false
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    
+    /// 用户钱包账户
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// 财库账户
+    /// 接收暂存的生成费用
+    #[account(
+        mut,
+        seeds = [Treasury::SEED, pool.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+    
+    // remaining_accounts: 被选中数字的NumberRecord PDA，未提供时之后通过register_number创建
+    // 随机数回填后客户端即可算出被选中的数字
+}
+
+/// 完成预言机生成处理函数
+/// 使用预言机回填的随机数发放数字，并将暂存的费用转入财库
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, CompleteVrfGeneration<'info>>) -> Result<()> {
+    require!(
+        ctx.accounts.vrf_request.fulfilled,
+        LuckSnakeError::VrfRequestNotFulfilled
+    );
+    
+    let random_value = randomness::random_u32(&[
+        &ctx.accounts.vrf_request.randomness,
+        ctx.accounts.user.key().as_ref(),
+    ]);
+    
+    // 挑选空闲数字并创建数字记录
+    let final_number = issue_number(
+        &mut *ctx.accounts.number_pool.load_mut()?,
+        &ctx.accounts.pool,
+        random_value,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.user.key(),
+    )?;
+    
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.numbers.push(final_number);
+    user_account.nonce += 1;
+    
+    // 暂存的费用转入财库，请求账户的租金在关闭时退还给用户
+    let amount = ctx.accounts.vrf_request.amount;
+    ctx.accounts.vrf_request.sub_lamports(amount)?;
+    ctx.accounts.treasury.add_lamports(amount)?;
+    
+    // 更新数字池计数器，费用计入下一次开奖的奖池
+    ctx.accounts.pool.total_generated_numbers += 1;
+    ctx.accounts.pool.record_revenue(amount, 0);
+    
+    emit!(NumberGenerated {
+        pool: ctx.accounts.pool.key(),
+        number: final_number,
+        owner: ctx.accounts.user.key(),
+        slot: Clock::get()?.slot,
+        price_paid: amount,
+        total_generated_numbers: ctx.accounts.pool.total_generated_numbers,
+        user_number_count: ctx.accounts.user_account.numbers.len() as u32,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::PoolCreated;
+
+/// 创建数字池指令的账户结构
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct CreatePool<'info> {
+    /// 程序配置账户
+    /// 只有程序管理员可以创建数字池
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    /// 使用数字池编号派生PDA，编号不能重复
+    #[account(
+        init,
+        payer = authority,
+        space = Pool::LEN,
+        seeds = [Pool::SEED, &pool_id.to_le_bytes()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 已发放数字位图账户
+    /// 记录本池哪些数字已被发放，容量取自数字范围
+    #[account(
+        init,
+        payer = authority,
+        space = NumberPool::LEN,
+        seeds = [NumberPool::SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub number_pool: AccountLoader<'info, NumberPool>,
+    
+    /// 财库账户
+    /// 由本程序拥有的PDA，从数字池地址派生，用于接收本池用户支付的费用
+    #[account(
+        init,
+        payer = authority,
+        space = Treasury::LEN,
+        seeds = [Treasury::SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    /// 提取日志账户
+    /// 环形缓冲区，记录本池最近的提取
+    #[account(
+        init,
+        payer = authority,
+        space = WithdrawalLog::LEN,
+        seeds = [WithdrawalLog::SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub withdrawal_log: AccountLoader<'info, WithdrawalLog>,
+    
+    /// 程序管理员账户
+    /// 需要签名，支付账户创建费用
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 创建数字池处理函数
+/// 设置数字池的管理员、价格和数字范围[range_min, range_max]
+pub fn handler(
+    ctx: Context<CreatePool>,
+    pool_id: u64,
+    pool_authority: Pubkey,
+    generation_price: u64,
+    range_min: u32,
+    range_max: u32,
+) -> Result<()> {
+    require!(
+        (Pool::MIN_GENERATION_PRICE..=Pool::MAX_GENERATION_PRICE).contains(&generation_price),
+        LuckSnakeError::InvalidGenerationPrice
+    );
+    
+    // 范围不能为空，且数字个数不能超过位图容量
+    require!(
+        range_min <= range_max && range_max - range_min < Pool::MAX_NUMBERS,
+        LuckSnakeError::InvalidNumberRange
+    );
+    
+    let pool = &mut ctx.accounts.pool;
+    pool.pool_id = pool_id;
+    pool.authority = pool_authority;
+    pool.generation_price = generation_price;
+    pool.total_generated_numbers = 0;
+    pool.range_min = range_min;
+    pool.range_max = range_max;
+    pool.treasury = ctx.accounts.treasury.key();
+    pool.bump = ctx.bumps.pool;
+    pool.round_count = 0;
+    pool.round_close_time = 0;
+    pool.round_pot = 0;
+    pool.prize_tiers = Pool::DEFAULT_PRIZE_TIERS;
+    pool.reserved_prizes = 0;
+    pool.specific_number_premium_bps = Pool::BPS_DENOMINATOR as u32;
+    pool.payment_mint = Pubkey::default();
+    pool.token_price = 0;
+    pool.price_curve = PriceCurve::Flat;
+    pool.price_curve_slope = 0;
+    pool.referral_bps = 0;
+    pool.treasury_revenue = 0;
+    pool.referral_revenue = 0;
+    pool.withdrawal_recipient = Pubkey::default();
+    
+    ctx.accounts.treasury.bump = ctx.bumps.treasury;
+    
+    // 初始化数字位图，所有数字均未发放
+    let mut number_pool = ctx.accounts.number_pool.load_init()?;
+    number_pool.capacity = pool.number_count();
+    number_pool.bump = ctx.bumps.number_pool;
+    
+    // 初始化提取日志
+    ctx.accounts.withdrawal_log.load_init()?.bump = ctx.bumps.withdrawal_log;
+    
+    emit!(PoolCreated {
+        pool: pool.key(),
+        pool_id,
+        authority: pool_authority,
+        treasury: pool.treasury,
+        generation_price,
+        range_min,
+        range_max,
+    });
+    
+    Ok(())
+}
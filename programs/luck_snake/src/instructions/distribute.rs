@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::{FeeRecipientPaid, FeesDistributed};
+
+/// 分账指令的账户结构
+/// 任何人都可以调用，接收方的钱包账户通过remaining_accounts传入
+#[derive(Accounts)]
+pub struct Distribute<'info> {
+    /// 程序配置账户
+    /// 保存分账表，并暂存已分得但尚未支付的lamports
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = config.has_fee_split() @ LuckSnakeError::FeeSplitNotSet
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    /// 验证财库地址
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = treasury
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 财库账户
+    /// 可分配的资金从这里转出
+    #[account(
+        mut,
+        seeds = [Treasury::SEED, pool.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+/// 分账处理函数
+/// 先把财库中可分配的资金按份额记入各接收方的待支付金额，再向remaining_accounts中的接收方支付全部待支付金额
+/// 与提取资金相同，财库保留免租金最低余额、已开奖未领取的奖金和进行中轮次的奖池，份额取整后的零头留在财库中
+/// 没有传入的接收方的金额继续保存在配置账户中，之后再次调用时支付
+pub fn handler(ctx: Context<Distribute>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let config = &mut ctx.accounts.config;
+    
+    let treasury_info = ctx.accounts.treasury.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury_info.data_len());
+    let distributable = treasury_info
+        .lamports()
+        .saturating_sub(rent_exempt_minimum)
+        .saturating_sub(pool.locked_funds());
+    
+    // 按份额记入各接收方的待支付金额
+    let mut amount = 0u64;
+    for index in 0..LuckSnakeConfig::MAX_FEE_RECIPIENTS {
+        let share = (distributable as u128 * config.fee_recipients[index].share_bps as u128
+            / Pool::BPS_DENOMINATOR as u128) as u64;
+        config.fee_accrued[index] += share;
+        amount += share;
+    }
+    if amount > 0 {
+        // 财库和配置账户都由本程序拥有，直接修改lamports完成转账
+        ctx.accounts.treasury.sub_lamports(amount)?;
+        config.add_lamports(amount)?;
+    }
+    
+    // 向传入的接收方支付，每个接收方只需传入一次
+    let mut paid = 0u64;
+    for recipient in ctx.remaining_accounts {
+        let Some(index) = config
+            .fee_recipients
+            .iter()
+            .position(|entry| entry.share_bps > 0 && entry.recipient == recipient.key())
+        else {
+            continue;
+        };
+        let recipient_amount = config.fee_accrued[index];
+        if recipient_amount == 0 {
+            continue;
+        }
+        config.fee_accrued[index] = 0;
+        config.sub_lamports(recipient_amount)?;
+        recipient.add_lamports(recipient_amount)?;
+        paid += recipient_amount;
+        
+        emit!(FeeRecipientPaid {
+            recipient: recipient.key(),
+            amount: recipient_amount,
+        });
+    }
+    
+    require!(amount > 0 || paid > 0, LuckSnakeError::NothingToDistribute);
+    
+    emit!(FeesDistributed {
+        pool: pool.key(),
+        amount,
+        paid,
+        fee_accrued: config.fee_accrued,
+    });
+    
+    Ok(())
+}
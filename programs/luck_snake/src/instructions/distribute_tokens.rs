@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::{TokenFeeRecipientPaid, TokenFeesDistributed};
+
+/// 代币分账指令的账户结构
+/// 任何人都可以调用，接收方的代币账户通过remaining_accounts传入
+#[derive(Accounts)]
+pub struct DistributeTokens<'info> {
+    /// 程序配置账户
+    /// 保存分账表和仍有待支付代币的代币分账账户数量
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = config.has_fee_split() @ LuckSnakeError::FeeSplitNotSet
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    /// 作为财库代币账户的权限签名
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 代币的mint账户
+    /// 可以是当前或以前使用过的支付代币
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    
+    /// 财库代币账户
+    /// 可分配的代币和待支付的代币都保存在这里
+    #[account(
+        mut,
+        seeds = [Pool::TREASURY_TOKEN_SEED, pool.key().as_ref(), payment_mint.key().as_ref()],
+        bump
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// 代币分账账户
+    /// 首次分配该数字池的该代币时创建
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TokenFeeAccrual::LEN,
+        seeds = [TokenFeeAccrual::SEED, pool.key().as_ref(), payment_mint.key().as_ref()],
+        bump
+    )]
+    pub token_fee_accrual: Account<'info, TokenFeeAccrual>,
+    
+    /// 付款人账户
+    /// 需要签名，支付代币分账账户的租金
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// mint所属的SPL Token或Token-2022程序
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 代币分账处理函数
+/// 先把财库代币账户中尚未分配的代币按份额记入各接收方的待支付数量，再向remaining_accounts中的接收方代币账户支付
+/// 接收方代币账户的所有者必须是分账表中的接收方，mint必须与本次分配的代币一致，不符合的账户被忽略
+/// 份额取整后的零头和没有传入的接收方的待支付代币留在财库代币账户中，之后再次调用时处理
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, DistributeTokens<'info>>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let config = &mut ctx.accounts.config;
+    let token_fee_accrual = &mut ctx.accounts.token_fee_accrual;
+    let mint = ctx.accounts.payment_mint.key();
+    
+    token_fee_accrual.pool = pool.key();
+    token_fee_accrual.mint = mint;
+    token_fee_accrual.bump = ctx.bumps.token_fee_accrual;
+    let had_accrued = token_fee_accrual.total_accrued() > 0;
+    
+    // 待支付的代币仍在财库代币账户中，只分配其余部分
+    let distributable = ctx
+        .accounts
+        .treasury_token_account
+        .amount
+        .saturating_sub(token_fee_accrual.total_accrued());
+    
+    // 按份额记入各接收方的待支付数量
+    let mut amount = 0u64;
+    for index in 0..LuckSnakeConfig::MAX_FEE_RECIPIENTS {
+        let share = (distributable as u128 * config.fee_recipients[index].share_bps as u128
+            / Pool::BPS_DENOMINATOR as u128) as u64;
+        token_fee_accrual.accrued[index] += share;
+        amount += share;
+    }
+    
+    // 向传入的接收方代币账户支付，每个接收方只需传入一次
+    let pool_id_bytes = pool.pool_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[Pool::SEED, &pool_id_bytes, &[pool.bump]]];
+    let mut paid = 0u64;
+    for recipient_token_account in ctx.remaining_accounts {
+        let Ok(token_account) = InterfaceAccount::<TokenAccount>::try_from(recipient_token_account) else {
+            continue;
+        };
+        if token_account.mint != mint {
+            continue;
+        }
+        let Some(index) = config
+            .fee_recipients
+            .iter()
+            .position(|entry| entry.share_bps > 0 && entry.recipient == token_account.owner)
+        else {
+            continue;
+        };
+        let recipient_amount = token_fee_accrual.accrued[index];
+        if recipient_amount == 0 {
+            continue;
+        }
+        token_fee_accrual.accrued[index] = 0;
+        
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.treasury_token_account.to_account_info(),
+                    mint: ctx.accounts.payment_mint.to_account_info(),
+                    to: recipient_token_account.clone(),
+                    authority: pool.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            recipient_amount,
+            ctx.accounts.payment_mint.decimals,
+        )?;
+        paid += recipient_amount;
+        
+        emit!(TokenFeeRecipientPaid {
+            recipient: token_account.owner,
+            mint,
+            recipient_token_account: recipient_token_account.key(),
+            amount: recipient_amount,
+        });
+    }
+    
+    require!(amount > 0 || paid > 0, LuckSnakeError::NothingToDistribute);
+    
+    // 维护仍有待支付代币的代币分账账户数量，不为0时不能修改分账表
+    let has_accrued = token_fee_accrual.total_accrued() > 0;
+    if has_accrued && !had_accrued {
+        config.token_fee_accrual_count += 1;
+    } else if had_accrued && !has_accrued {
+        config.token_fee_accrual_count -= 1;
+    }
+    
+    emit!(TokenFeesDistributed {
+        pool: pool.key(),
+        mint,
+        amount,
+        paid,
+        accrued: token_fee_accrual.accrued,
+    });
+    
+    Ok(())
+}
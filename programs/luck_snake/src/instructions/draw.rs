@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::randomness;
+use crate::events::RoundDrawn;
+
+/// 开奖指令的账户结构
+/// 任何人都可以在请求开奖的目标slot之后调用
+#[derive(Accounts)]
+pub struct Draw<'info> {
+    /// 数字池账户
+    /// 开奖后清除进行中的轮次，并记录待领取的奖金
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 轮次账户
+    #[account(
+        mut,
+        seeds = [Round::SEED, pool.key().as_ref(), &round.round_id.to_le_bytes()],
+        bump = round.bump,
+        has_one = pool,
+        constraint = !round.drawn @ LuckSnakeError::RoundAlreadyDrawn
+    )]
+    pub round: Account<'info, Round>,
+    
+    /// 已发放数字位图账户
+    /// 用于统计各奖级命中的已发放数字
+    #[account(
+        seeds = [NumberPool::SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub number_pool: AccountLoader<'info, NumberPool>,
+    
+    /// SlotHashes系统变量
+    /// CHECK: 通过address约束验证为SlotHashes sysvar
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+/// 开奖处理函数
+/// 使用请求开奖时记录的目标slot的区块哈希挑选中奖数字，并按开启轮次时保存的奖级表计算每个奖级的奖金
+/// 请求过期后使用最新的区块哈希
+pub fn handler(ctx: Context<Draw>) -> Result<()> {
+    let clock = Clock::get()?;
+    let pool = &mut ctx.accounts.pool;
+    let round = &mut ctx.accounts.round;
+    
+    require!(round.is_draw_requested(), LuckSnakeError::DrawNotRequested);
+    require!(clock.slot > round.draw_slot, LuckSnakeError::DrawSlotNotReached);
+    
+    // 截止之后不再发放数字，目标slot在请求开奖时才确定，调用者无法挑选对自己有利的区块哈希
+    // 请求过期后不能重新请求，改用开奖交易所在slot能看到的最新区块哈希，不再给出可以放弃的新结果
+    let slot_hash = if round.is_draw_expired(clock.slot) {
+        randomness::latest_slot_hash(&ctx.accounts.slot_hashes)?
+    } else {
+        randomness::slot_hash_at_or_after(&ctx.accounts.slot_hashes, round.draw_slot)?
+    };
+    let random_value = randomness::random_u32(&[
+        &slot_hash,
+        round.key().as_ref(),
+    ]);
+    let index = random_value % pool.number_count();
+    
+    round.winning_number = pool.range_min + index;
+    round.pot = pool.round_pot;
+    round.drawn = true;
+    pool.round_close_time = 0;
+    
+    // 保存已发放数字的快照，开奖之后发放的数字不能领取本轮奖金
+    let number_pool = ctx.accounts.number_pool.load()?;
+    round.eligible = number_pool.bitmap;
+    
+    // 统计已发放的数字中命中各奖级的个数，每个数字只计入它命中的最高奖级
+    let mut tier_winners = [0u32; Pool::MAX_PRIZE_TIERS];
+    for issued_index in 0..number_pool.capacity {
+        if !round.is_eligible(issued_index) {
+            continue;
+        }
+        if let Some(tier) = round.winning_tier(pool.range_min + issued_index) {
+            tier_winners[tier] += 1;
+        }
+    }
+    
+    // 有命中数字的奖级由命中数字平分其份额并从财库中预留，提取资金时不能动用
+    // 没有命中数字的奖级份额滚入下一轮奖池，剩余部分归数字池所有
+    let mut rolled_over = 0u64;
+    let prize_tiers = round.prize_tiers;
+    for (tier, prize_tier) in prize_tiers.iter().enumerate() {
+        let tier_share = (round.pot as u128 * prize_tier.share_bps as u128
+            / Pool::BPS_DENOMINATOR as u128) as u64;
+        if tier_winners[tier] == 0 {
+            rolled_over += tier_share;
+            continue;
+        }
+        let prize = tier_share / tier_winners[tier] as u64;
+        round.tier_prizes[tier] = prize;
+        pool.reserved_prizes += prize * tier_winners[tier] as u64;
+    }
+    round.tier_winners = tier_winners;
+    pool.round_pot = rolled_over;
+    
+    emit!(RoundDrawn {
+        pool: pool.key(),
+        round: round.key(),
+        round_id: round.round_id,
+        winning_number: round.winning_number,
+        pot: round.pot,
+        tier_winners: round.tier_winners,
+        tier_prizes: round.tier_prizes,
+        rolled_over,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::VrfFulfilled;
+
+/// 预言机回填随机数指令的账户结构
+/// 账户顺序需与oracle::FULFILL_CALLBACK_IX的约定一致
+#[derive(Accounts)]
+pub struct FulfillVrfGeneration<'info> {
+    /// 预言机授权账户
+    /// 必须是配置中设置的预言机授权地址
+    #[account(
+        constraint = oracle_authority.key() == config.oracle_authority @ LuckSnakeError::Unauthorized
+    )]
+    pub oracle_authority: Signer<'info>,
+    
+    /// 程序配置账户
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 随机数请求账户
+    #[account(
+        mut,
+        seeds = [VrfRequest::SEED, vrf_request.pool.as_ref(), vrf_request.user.as_ref()],
+        bump = vrf_request.bump
+    )]
+    pub vrf_request: Account<'info, VrfRequest>,
+}
+
+/// 预言机回填随机数处理函数
+/// 只记录随机数，数字由用户随后调用complete_vrf_generation领取
+pub fn handler(ctx: Context<FulfillVrfGeneration>, randomness: [u8; 32]) -> Result<()> {
+    let vrf_request = &mut ctx.accounts.vrf_request;
+    
+    require!(
+        !vrf_request.fulfilled,
+        LuckSnakeError::VrfRequestAlreadyFulfilled
+    );
+    
+    // 超时的请求可能已被用户取消退款，不再接受回填
+    let slot = Clock::get()?.slot;
+    require!(
+        !vrf_request.is_timed_out(slot),
+        LuckSnakeError::VrfRequestTimedOut
+    );
+    
+    vrf_request.randomness = randomness;
+    vrf_request.fulfilled = true;
+    
+    emit!(VrfFulfilled {
+        pool: vrf_request.pool,
+        user: vrf_request.user,
+        slot,
+    });
+    
+    Ok(())
+}
@@ -1,31 +1,58 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
-use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use crate::state::*;
 use crate::errors::LuckSnakeError;
+use crate::randomness;
+use crate::events::{NumberGenerated, ReferralRewardAccrued};
 
 /// 生成数字指令的账户结构
 /// 定义生成随机数字所需的所有账户
 #[derive(Accounts)]
 pub struct GenerateNumber<'info> {
     /// 程序配置账户
-    /// 包含价格、计数等全局配置信息
+    /// 检查全局暂停状态
     #[account(
-        mut,                                    // 需要修改（更新计数器）
         seeds = [LuckSnakeConfig::SEED],       // 使用PDA种子验证
-        bump                                    // 验证bump值
+        bump,                                   // 验证bump值
+        constraint = !config.paused @ LuckSnakeError::GenerationPaused // 暂停期间拒绝生成
     )]
     pub config: Account<'info, LuckSnakeConfig>,
     
+    /// 数字池账户
+    /// 包含价格、数字范围和发放计数
+    #[account(
+        mut,                                    // 需要修改（更新计数器）
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 已发放数字位图账户
+    /// 用于直接挑选空闲数字并标记为已发放
+    #[account(
+        mut,
+        seeds = [NumberPool::SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub number_pool: AccountLoader<'info, NumberPool>,
+    
     /// 用户账户
-    /// 存储用户拥有的所有数字，动态增长
-    /// CHECK: PDA账户使用seeds和bump约束验证，手动处理序列化以支持动态大小调整
+    /// 存储用户拥有的所有数字，需先通过init_user创建
+    /// 每次生成扩容一个数字的空间，新增租金由用户支付
     #[account(
         mut,
-        seeds = [UserAccount::SEED, user.key().as_ref()], // PDA种子包含用户地址
-        bump                            // 自动计算bump值
+        seeds = [UserAccount::SEED, pool.key().as_ref(), user.key().as_ref()], // PDA种子包含数字池和用户地址
+        bump = user_account.bump,                         // 使用存储的bump值
+        has_one = user,                                   // 验证账户属于该用户
+        realloc = user_account.space_after_adding_number(),
+        realloc::payer = user,
+        realloc::zero = false
     )]
-    pub user_account: AccountInfo<'info>,
+    pub user_account: Account<'info, UserAccount>,
     
     /// 用户钱包账户
     /// 需要签名，支付生成费用
@@ -36,208 +63,427 @@ pub struct GenerateNumber<'info> {
     /// 接收用户支付的费用
     #[account(
         mut,
-        constraint = treasury.key() == config.treasury @ LuckSnakeError::Unauthorized
+        seeds = [Treasury::SEED, pool.key().as_ref()],
+        bump = treasury.bump
     )]
-    pub treasury: SystemAccount<'info>,
+    pub treasury: Account<'info, Treasury>,
     
     /// 系统程序
     /// 用于转账操作
     pub system_program: Program<'info, System>,
-}
-
-/// 生成数字处理函数
-/// 为用户生成一个唯一的随机数字
-pub fn handler(ctx: Context<GenerateNumber>) -> Result<()> {
-    // 获取账户引用
-    let config = &mut ctx.accounts.config;
     
-    // 检查是否还有剩余数字可生成
-    require!(
-        config.total_generated_numbers < LuckSnakeConfig::MAX_NUMBERS,
-        LuckSnakeError::AllNumbersGenerated
-    );
-    
-    // 创建转账指令，从用户账户转账到财库
-    let transfer_instruction = system_program::Transfer {
-        from: ctx.accounts.user.to_account_info(),
-        to: ctx.accounts.treasury.to_account_info(),
-    };
+    /// 支付代币的mint账户
+    /// 以下代币支付账户仅在数字池以代币支付时需要提供
+    #[account(address = pool.payment_mint @ LuckSnakeError::InvalidPaymentMint)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
     
-    // 创建CPI上下文
-    let transfer_context = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
-        transfer_instruction,
-    );
-    
-    // 执行转账，收取生成费用
-    system_program::transfer(transfer_context, config.generation_price)?;
-    
-    // 获取当前时钟，用于生成随机数
-    let clock = Clock::get()?;
-    
-    // 获取当前 nonce 值 - 我们稍后会重新获取，这里先用0
-    let current_nonce: u64 = 0;
-    
-    // 创建随机种子
-    // 使用slot、用户nonce和用户地址作为熵源
-    let mut random_seed = [0u8; 32];
-    random_seed[..8].copy_from_slice(&clock.slot.to_le_bytes());
-    random_seed[8..16].copy_from_slice(&current_nonce.to_le_bytes());
-    random_seed[16..].copy_from_slice(&ctx.accounts.user.key().to_bytes()[..16]);
-    
-    // 使用Keccak哈希生成随机数
-    let hash = anchor_lang::solana_program::keccak::hash(&random_seed);
-    let hash_bytes = hash.to_bytes();
-    
-    // 从哈希值中提取32位整数
-    let random_value = u32::from_le_bytes([
-        hash_bytes[0],
-        hash_bytes[1], 
-        hash_bytes[2],
-        hash_bytes[3],
-    ]);
+    /// 用户支付费用的代币账户
+    #[account(
+        mut,
+        constraint = user_token_account.mint == pool.payment_mint @ LuckSnakeError::InvalidPaymentMint
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     
-    // 将随机值限制在0-999范围内
-    let random_number = random_value % 1000;
+    /// 财库代币账户
+    /// 接收用户支付的代币
+    #[account(
+        mut,
+        seeds = [Pool::TREASURY_TOKEN_SEED, pool.key().as_ref(), pool.payment_mint.as_ref()],
+        bump
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     
-    // 尝试找到一个用户还未拥有的数字
-    let mut attempts = 0;
-    let max_attempts = 100;  // 最多尝试100次
-    let mut final_number = random_number;
+    /// SPL Token或Token-2022程序
+    pub token_program: Option<Interface<'info, TokenInterface>>,
     
-    // 循环直到找到一个唯一的数字
-    while attempts < max_attempts {
-        let mut already_exists = false;
-        
-        // 检查用户是否已经拥有这个数字 - 我们稍后会处理，现在跳过
-        already_exists = false;
-        
-        // 如果数字不存在，使用它
-        if !already_exists {
-            break;
-        }
-        
-        // 尝试下一个数字（循环0-999）
-        final_number = (final_number + 1) % 1000;
-        attempts += 1;
-    }
+    /// 推荐人的推荐奖励账户
+    /// 可选，提供时按数字池的推荐比例把一部分费用转入该账户，推荐人不能是用户自己
+    #[account(
+        mut,
+        seeds = [ReferralRewards::SEED, pool.key().as_ref(), referral_rewards.referrer.as_ref()],
+        bump = referral_rewards.bump,
+        constraint = referral_rewards.referrer != user.key() @ LuckSnakeError::SelfReferral
+    )]
+    pub referral_rewards: Option<Account<'info, ReferralRewards>>,
     
-    // 如果无法找到唯一数字，返回错误
+    // remaining_accounts: 可能被选中数字的NumberRecord PDA（可选）
+    // 程序根据随机值从位图中挑选数字后，在其中查找对应的记录账户并创建，未提供时之后通过register_number创建
+}
+
+/// 检查数字记录账户是否已经创建
+pub(crate) fn number_record_exists(number_record: &AccountInfo) -> bool {
+    number_record.owner == &crate::ID && !number_record.data_is_empty()
+}
+
+/// 创建数字记录PDA并写入所有者信息
+/// 兼容PDA地址已被预先转入lamports的情况（此时create_account会失败）
+pub(crate) fn create_number_record<'info>(
+    number_record: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    pool: Pubkey,
+    number: u32,
+    owner: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    // 记录已存在说明该数字已经属于某个用户
     require!(
-        attempts < max_attempts,
-        LuckSnakeError::UnableToGenerateUniqueNumber
+        !number_record_exists(number_record),
+        LuckSnakeError::NumberAlreadyGenerated
     );
     
-    // 检查账户状态
-    let needs_init;
-    let current_numbers_len;
-    
-    if ctx.accounts.user_account.data_len() < UserAccount::BASE_LEN {
-        // 账户不存在或太小，需要初始化
-        needs_init = true;
-        current_numbers_len = 0;
-        
-        // 创建账户
-        let rent = anchor_lang::prelude::Rent::get()?;
-        let required_lamports = rent.minimum_balance(UserAccount::BASE_LEN);
-        
-        // 创建账户指令
-        anchor_lang::system_program::create_account(
-            anchor_lang::context::CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                anchor_lang::system_program::CreateAccount {
-                    from: ctx.accounts.user.to_account_info(),
-                    to: ctx.accounts.user_account.to_account_info(),
+    let number_bytes = number.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[NumberRecord::SEED, pool.as_ref(), &number_bytes, &[bump]]];
+    let rent = Rent::get()?;
+    let required_lamports = rent.minimum_balance(NumberRecord::LEN);
+    
+    if number_record.lamports() == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: number_record.clone(),
                 },
-                &[&[
-                    UserAccount::SEED,
-                    ctx.accounts.user.key().as_ref(),
-                    &[ctx.bumps.user_account],
-                ]],
+                signer_seeds,
             ),
             required_lamports,
-            UserAccount::BASE_LEN as u64,
+            NumberRecord::LEN as u64,
             &crate::ID,
         )?;
     } else {
-        // 账户存在，反序列化检查
-        let mut user_account_data = &ctx.accounts.user_account.try_borrow_data()?[8..]; // 跳过discriminator
-        let user_account: UserAccount = anchor_lang::AnchorDeserialize::deserialize(&mut user_account_data)?;
-        needs_init = user_account.user == Pubkey::default();
-        current_numbers_len = user_account.numbers.len();
+        // 补足租金后分配空间并将所有权交给本程序
+        let top_up = required_lamports.saturating_sub(number_record.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: number_record.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: number_record.clone(),
+                },
+                signer_seeds,
+            ),
+            NumberRecord::LEN as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: number_record.clone(),
+                },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )?;
     }
     
-    // 现在支持动态增长，不再限制数字数量（理论上最多受账户大小限制约10MB）
-    
-    // 手动处理账户数据
-    let mut user_account_data = ctx.accounts.user_account.try_borrow_mut_data()?;
+    let record = NumberRecord {
+        pool,
+        number,
+        owner,
+        generated_at: Clock::get()?.unix_timestamp,
+        bump,
+    };
+    let mut record_data = number_record.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut record_data;
+    record.try_serialize(&mut writer)?;
     
-    // 反序列化现有账户（跳过8字节discriminator）
-    let mut user_account: UserAccount = if needs_init {
-        // 创建新的用户账户（不手动写入discriminator，try_serialize会处理）
-        UserAccount {
-            user: ctx.accounts.user.key(),
-            numbers: Vec::new(),
-            nonce: 0,
-            bump: ctx.bumps.user_account,
+    Ok(())
+}
+
+/// 检查价格是否超过用户愿意支付的最高价格
+/// 价格曲线下价格随发放数量上涨，用户通过max_price防止交易执行前被他人抢先推高价格
+pub(crate) fn check_max_price(price: u64, max_price: Option<u64>) -> Result<()> {
+    if let Some(max_price) = max_price {
+        require!(price <= max_price, LuckSnakeError::PriceExceedsMax);
+    }
+    Ok(())
+}
+
+/// 代币支付使用的账户
+/// 数字池以lamports支付时可以全部省略
+pub(crate) struct TokenPaymentAccounts<'a, 'info> {
+    pub payment_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    pub user_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub treasury_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<&'a Interface<'info, TokenInterface>>,
+}
+
+/// 检查支付代币的mint是否带有不支持的扩展
+/// 不可转让的代币无法支付；永久委托人可以随时转走财库中的代币；转账钩子需要额外的账户
+pub(crate) fn check_payment_mint_extensions(payment_mint: &AccountInfo) -> Result<()> {
+    let mint_data = payment_mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    for extension in mint.get_extension_types()? {
+        match extension {
+            ExtensionType::NonTransferable => return err!(LuckSnakeError::NonTransferableMint),
+            ExtensionType::PermanentDelegate => return err!(LuckSnakeError::PermanentDelegateMint),
+            ExtensionType::TransferHook => return err!(LuckSnakeError::TransferHookMint),
+            _ => {}
         }
-    } else {
-        let mut data_slice = &user_account_data[8..];
-        anchor_lang::AnchorDeserialize::deserialize(&mut data_slice)?
+    }
+    Ok(())
+}
+
+/// 计算需要从用户转出的代币数量
+/// mint带有转账手续费扩展时按当前epoch的费率加上手续费，保证财库至少收到amount
+fn gross_payment_amount(payment_mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let mint_data = payment_mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
     };
+    transfer_fee_config
+        .get_epoch_fee(Clock::get()?.epoch)
+        .calculate_pre_fee_amount(amount)
+        .ok_or(error!(LuckSnakeError::TransferFeeOverflow))
+}
+
+/// 收取生成费用
+/// 数字池以代币支付时从用户的代币账户转入财库代币账户，否则以lamports转入财库
+/// 以lamports支付且提供了推荐奖励账户时，按推荐比例把一部分费用转入推荐奖励账户
+/// max_price与用户实际转出的金额比较，代币带转账手续费时包含手续费
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn collect_payment<'info>(
+    pool: &mut Pool,
+    user: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_accounts: TokenPaymentAccounts<'_, 'info>,
+    referral_rewards: Option<&mut Account<'info, ReferralRewards>>,
+    amount: u64,
+    max_price: Option<u64>,
+) -> Result<()> {
+    if pool.pays_with_token() {
+        // 推荐奖励以lamports记账和领取，代币支付的数字池不支持推荐
+        require!(referral_rewards.is_none(), LuckSnakeError::TokenPaymentNotSupported);
+        return collect_token_payment(user, token_accounts, amount, max_price);
+    }
     
-    // 将新数字添加到用户账户
-    user_account.numbers.push(final_number);
+    check_max_price(amount, max_price)?;
     
-    // 增加用户的nonce值，用于下次生成不同的随机数
-    user_account.nonce += 1;
+    let referral_amount = if referral_rewards.is_some() {
+        pool.referral_share(amount)
+    } else {
+        0
+    };
+    let treasury_amount = amount - referral_amount;
+    transfer_lamports(system_program, user, treasury, treasury_amount)?;
+    
+    if let Some(referral_rewards) = referral_rewards {
+        // 推荐奖励保存在推荐奖励账户中，推荐人之后自行领取
+        transfer_lamports(system_program, user, &referral_rewards.to_account_info(), referral_amount)?;
+        referral_rewards.accrued += referral_amount;
+        referral_rewards.total_earned += referral_amount;
+        
+        emit!(ReferralRewardAccrued {
+            pool: referral_rewards.pool,
+            referrer: referral_rewards.referrer,
+            user: user.key(),
+            amount: referral_amount,
+            treasury_amount,
+            accrued: referral_rewards.accrued,
+        });
+    }
     
-    // 释放借用，以便进行账户大小调整
-    drop(user_account_data);
+    pool.record_revenue(treasury_amount, referral_amount);
     
-    // 现在根据最终的数字数量计算所需大小
-    let final_numbers_count = user_account.numbers.len();
-    let required_size = UserAccount::space_for_numbers(final_numbers_count);
-    let current_size = ctx.accounts.user_account.to_account_info().data_len();
+    Ok(())
+}
+
+/// 从用户转出lamports
+fn transfer_lamports<'info>(
+    system_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// 以代币收取生成费用
+/// amount为财库实际收到的数量，转账手续费由用户额外承担，因此max_price与含手续费的转出数量比较
+fn collect_token_payment<'info>(
+    user: &AccountInfo<'info>,
+    token_accounts: TokenPaymentAccounts<'_, 'info>,
+    amount: u64,
+    max_price: Option<u64>,
+) -> Result<()> {
+    let (Some(payment_mint), Some(user_token_account), Some(treasury_token_account), Some(token_program)) = (
+        token_accounts.payment_mint,
+        token_accounts.user_token_account,
+        token_accounts.treasury_token_account,
+        token_accounts.token_program,
+    ) else {
+        return err!(LuckSnakeError::TokenPaymentAccountsMissing);
+    };
     
+    let mint_info = payment_mint.to_account_info();
+    check_payment_mint_extensions(&mint_info)?;
+    let gross_amount = gross_payment_amount(&mint_info, amount)?;
+    check_max_price(gross_amount, max_price)?;
+    
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: user_token_account.to_account_info(),
+                mint: mint_info,
+                to: treasury_token_account.to_account_info(),
+                authority: user.clone(),
+            },
+        ),
+        gross_amount,
+        payment_mint.decimals,
+    )
+}
+
+/// 根据随机值发放一个数字
+/// 从位图中挑选第k个空闲位置，换算为数字池范围内的数字并在位图中标记
+/// 被选中数字的记录账户出现在remaining_accounts中时同时创建NumberRecord，
+/// 否则之后由所有者通过register_number创建，唯一性由位图保证，发放不会因缺少账户而失败
+pub(crate) fn issue_number<'info>(
+    number_pool: &mut NumberPool,
+    pool: &Account<Pool>,
+    random_value: u32,
+    remaining_accounts: &[AccountInfo<'info>],
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    owner: Pubkey,
+) -> Result<u32> {
+    // 进行中的轮次截止之后、开奖之前不再发放数字
+    require!(
+        pool.is_accepting_numbers(Clock::get()?.unix_timestamp),
+        LuckSnakeError::RoundClosed
+    );
     
-    if required_size > current_size {
-        // 计算所需的额外rent
-        let rent = anchor_lang::prelude::Rent::get()?;
-        let new_rent = rent.minimum_balance(required_size);
-        let current_rent = rent.minimum_balance(current_size);
-        let additional_lamports = new_rent.saturating_sub(current_rent);
-        
-        if additional_lamports > 0 {
-            // 从用户账户转账到PDA以支付额外的rent
-            anchor_lang::system_program::transfer(
-                anchor_lang::context::CpiContext::new(
-                    ctx.accounts.system_program.to_account_info(),
-                    anchor_lang::system_program::Transfer {
-                        from: ctx.accounts.user.to_account_info(),
-                        to: ctx.accounts.user_account.to_account_info(),
-                    }
-                ),
-                additional_lamports,
-            )?;
-        }
-        
-        // 调整账户大小
-        ctx.accounts.user_account.to_account_info().resize(required_size)?;
+    // 在剩余的空闲数字中直接挑选第k个，无需逐个探测
+    let free_count = number_pool
+        .capacity
+        .checked_sub(pool.total_generated_numbers)
+        .filter(|free| *free > 0)
+        .ok_or(LuckSnakeError::AllNumbersGenerated)?;
+    let index = number_pool
+        .nth_free(random_value % free_count)
+        .ok_or(LuckSnakeError::UnableToGenerateUniqueNumber)?;
+    let number = pool.range_min + index;
+    
+    // 在客户端提供的账户中查找被选中数字的记录账户，找到时直接创建数字记录
+    let (record_key, record_bump) = Pubkey::find_program_address(
+        &[NumberRecord::SEED, pool.key().as_ref(), &number.to_le_bytes()],
+        &crate::ID,
+    );
+    if let Some(number_record) = remaining_accounts
+        .iter()
+        .find(|account| account.key() == record_key)
+    {
+        create_number_record(number_record, payer, system_program, pool.key(), number, owner, record_bump)?;
     }
     
-    // 重新获取可变借用进行序列化
-    let mut user_account_data = ctx.accounts.user_account.try_borrow_mut_data()?;
+    // 在位图中标记该数字已发放，确保"一个数字只有一个所有者"
+    number_pool.mark_issued(index);
+    
+    Ok(number)
+}
+
+/// 生成数字处理函数
+/// 为用户生成一个唯一的随机数字
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GenerateNumber<'info>>,
+    max_price: Option<u64>,
+) -> Result<()> {
+    // 获取账户引用
+    let pool = &mut ctx.accounts.pool;
+    
+    // 检查是否还有剩余数字可生成
+    require!(
+        pool.total_generated_numbers < pool.number_count(),
+        LuckSnakeError::AllNumbersGenerated
+    );
     
-    // 序列化回去 - Anchor的try_serialize包含discriminator，所以我们使用完整的缓冲区
-    let mut writer = &mut user_account_data[..]; // 使用完整缓冲区
-    user_account.try_serialize(&mut writer)?;
+    // 收取生成费用，转入财库或财库代币账户
+    let price = pool.current_price().ok_or(LuckSnakeError::PriceOverflow)?;
+    collect_payment(
+        pool,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.treasury.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        TokenPaymentAccounts {
+            payment_mint: ctx.accounts.payment_mint.as_ref(),
+            user_token_account: ctx.accounts.user_token_account.as_ref(),
+            treasury_token_account: ctx.accounts.treasury_token_account.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+        },
+        ctx.accounts.referral_rewards.as_mut(),
+        price,
+        max_price,
+    )?;
+    
+    let user_account = &mut ctx.accounts.user_account;
     
-    // 更新全局计数器
-    config.total_generated_numbers += 1;
+    // 获取当前时钟，用于生成随机数
+    let clock = Clock::get()?;
     
-    // 记录日志
-    msg!("生成数字: {} 给用户: {}", final_number, ctx.accounts.user.key());
+    // 使用slot、用户nonce和完整的32字节用户地址作为熵源
+    // nonce每次生成后递增，保证同一slot内多次生成得到不同的哈希
+    let random_value = randomness::random_u32(&[
+        &clock.slot.to_le_bytes(),
+        &user_account.nonce.to_le_bytes(),
+        ctx.accounts.user.key().as_ref(),
+    ]);
+    
+    // 挑选空闲数字并创建数字记录
+    let final_number = issue_number(
+        &mut *ctx.accounts.number_pool.load_mut()?,
+        pool,
+        random_value,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.user.key(),
+    )?;
+    
+    // 将新数字添加到用户账户（账户空间已由realloc约束扩容）
+    user_account.numbers.push(final_number);
+    
+    // 增加用户的nonce值，用于下次生成不同的随机数
+    user_account.nonce += 1;
+    
+    // 更新数字池计数器
+    pool.total_generated_numbers += 1;
+    
+    emit!(NumberGenerated {
+        pool: pool.key(),
+        number: final_number,
+        owner: ctx.accounts.user.key(),
+        slot: clock.slot,
+        price_paid: price,
+        total_generated_numbers: pool.total_generated_numbers,
+        user_number_count: user_account.numbers.len() as u32,
+    });
     
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::randomness;
+use crate::events::NumberGenerated;
+use super::generate_number::{collect_payment, issue_number, TokenPaymentAccounts};
+
+/// 批量生成数字指令的账户结构
+/// 与生成单个数字相同，用户账户一次扩容到最终大小
+#[derive(Accounts)]
+#[instruction(count: u8)]
+pub struct GenerateNumbers<'info> {
+    /// 程序配置账户
+    /// 检查全局暂停状态
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = !config.paused @ LuckSnakeError::GenerationPaused
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 已发放数字位图账户
+    #[account(
+        mut,
+        seeds = [NumberPool::SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub number_pool: AccountLoader<'info, NumberPool>,
+    
+    /// 用户账户
+    /// 一次扩容count个数字的空间，新增租金由用户支付
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+        has_one = user,
+        realloc = user_account.space_after_adding_numbers(count),
+        realloc::payer = user,
+        realloc::zero = false
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    
+    /// 用户钱包账户
+    /// 需要签名，支付全部生成费用
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// 财库账户
+    #[account(
+        mut,
+        seeds = [Treasury::SEED, pool.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+    
+    /// 支付代币的mint账户
+    /// 以下代币支付账户仅在数字池以代币支付时需要提供
+    #[account(address = pool.payment_mint @ LuckSnakeError::InvalidPaymentMint)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// 用户支付费用的代币账户
+    #[account(
+        mut,
+        constraint = user_token_account.mint == pool.payment_mint @ LuckSnakeError::InvalidPaymentMint
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// 财库代币账户
+    #[account(
+        mut,
+        seeds = [Pool::TREASURY_TOKEN_SEED, pool.key().as_ref(), pool.payment_mint.as_ref()],
+        bump
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// SPL Token或Token-2022程序
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    /// 推荐人的推荐奖励账户
+    /// 可选，提供时按数字池的推荐比例把一部分费用转入该账户，推荐人不能是用户自己
+    #[account(
+        mut,
+        seeds = [ReferralRewards::SEED, pool.key().as_ref(), referral_rewards.referrer.as_ref()],
+        bump = referral_rewards.bump,
+        constraint = referral_rewards.referrer != user.key() @ LuckSnakeError::SelfReferral
+    )]
+    pub referral_rewards: Option<Account<'info, ReferralRewards>>,
+    
+    // remaining_accounts: 每个可能被选中数字的NumberRecord PDA，未提供的记录之后通过register_number创建
+    // 第i个数字使用nonce + i生成，客户端可以依次预测
+}
+
+/// 批量生成数字处理函数
+/// 一次转账收取count个数字的费用，然后依次发放count个不重复的数字
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GenerateNumbers<'info>>,
+    count: u8,
+    max_price: Option<u64>,
+) -> Result<()> {
+    require!(
+        (1..=Pool::MAX_BATCH_COUNT).contains(&count),
+        LuckSnakeError::InvalidBatchCount
+    );
+    
+    let pool = &mut ctx.accounts.pool;
+    
+    // 检查剩余数字是否足够
+    require!(
+        pool.total_generated_numbers + count as u32 <= pool.number_count(),
+        LuckSnakeError::AllNumbersGenerated
+    );
+    
+    // 一次收取全部费用，价格曲线下每个数字按发放时的价格计算，max_price限制的是总价
+    let total_price = pool.batch_price(count).ok_or(LuckSnakeError::PriceOverflow)?;
+    collect_payment(
+        pool,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.treasury.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        TokenPaymentAccounts {
+            payment_mint: ctx.accounts.payment_mint.as_ref(),
+            user_token_account: ctx.accounts.user_token_account.as_ref(),
+            treasury_token_account: ctx.accounts.treasury_token_account.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+        },
+        ctx.accounts.referral_rewards.as_mut(),
+        total_price,
+        max_price,
+    )?;
+    
+    let user_account = &mut ctx.accounts.user_account;
+    let clock = Clock::get()?;
+    let mut number_pool = ctx.accounts.number_pool.load_mut()?;
+    
+    for _ in 0..count {
+        // 发放前的价格，即该数字计入总价的部分
+        let price = pool.current_price().ok_or(LuckSnakeError::PriceOverflow)?;
+        
+        // 与单个生成使用相同的熵源，nonce每发放一个数字递增一次
+        let random_value = randomness::random_u32(&[
+            &clock.slot.to_le_bytes(),
+            &user_account.nonce.to_le_bytes(),
+            ctx.accounts.user.key().as_ref(),
+        ]);
+        
+        let number = issue_number(
+            &mut number_pool,
+            pool,
+            random_value,
+            ctx.remaining_accounts,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.user.key(),
+        )?;
+        
+        user_account.numbers.push(number);
+        user_account.nonce += 1;
+        pool.total_generated_numbers += 1;
+        
+        emit!(NumberGenerated {
+            pool: pool.key(),
+            number,
+            owner: ctx.accounts.user.key(),
+            slot: clock.slot,
+            price_paid: price,
+            total_generated_numbers: pool.total_generated_numbers,
+            user_number_count: user_account.numbers.len() as u32,
+        });
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::events::ReferralInitialized;
+
+/// 注册推荐人指令的账户结构
+#[derive(Accounts)]
+pub struct InitReferral<'info> {
+    /// 数字池账户
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 推荐奖励账户
+    /// 每个推荐人在每个数字池中一个，推荐奖励以lamports保存在这里
+    #[account(
+        init,
+        payer = referrer,
+        space = ReferralRewards::LEN,
+        seeds = [ReferralRewards::SEED, pool.key().as_ref(), referrer.key().as_ref()],
+        bump
+    )]
+    pub referral_rewards: Account<'info, ReferralRewards>,
+    
+    /// 推荐人钱包账户
+    /// 需要签名，支付推荐奖励账户的租金
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 注册推荐人处理函数
+pub fn handler(ctx: Context<InitReferral>) -> Result<()> {
+    let referral_rewards = &mut ctx.accounts.referral_rewards;
+    referral_rewards.pool = ctx.accounts.pool.key();
+    referral_rewards.referrer = ctx.accounts.referrer.key();
+    referral_rewards.accrued = 0;
+    referral_rewards.total_earned = 0;
+    referral_rewards.bump = ctx.bumps.referral_rewards;
+    
+    emit!(ReferralInitialized {
+        pool: referral_rewards.pool,
+        referrer: referral_rewards.referrer,
+        referral_rewards: referral_rewards.key(),
+    });
+    
+    Ok(())
+}
@@ -1,16 +1,25 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::events::UserInitialized;
 
 /// 初始化用户账户的账户结构
 #[derive(Accounts)]
 pub struct InitUser<'info> {
+    /// 数字池账户
+    /// 用户账户属于该数字池
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
     /// 用户账户
     /// 创建新的用户账户
     #[account(
         init,                                   // 初始化新账户
         payer = user,                          // 用户支付创建费用
         space = UserAccount::BASE_LEN,         // 基础账户空间大小
-        seeds = [UserAccount::SEED, user.key().as_ref()], // PDA种子包含用户地址
+        seeds = [UserAccount::SEED, pool.key().as_ref(), user.key().as_ref()], // PDA种子包含数字池和用户地址
         bump                                   // 自动计算bump值
     )]
     pub user_account: Account<'info, UserAccount>,
@@ -30,12 +39,17 @@ pub fn handler(ctx: Context<InitUser>) -> Result<()> {
     let user_account = &mut ctx.accounts.user_account;
     
     // 初始化用户账户
+    user_account.pool = ctx.accounts.pool.key();
     user_account.user = ctx.accounts.user.key();
     user_account.numbers = Vec::new();
     user_account.nonce = 0;
     user_account.bump = ctx.bumps.user_account;
     
-    msg!("初始化用户账户: {}", ctx.accounts.user.key());
+    emit!(UserInitialized {
+        pool: user_account.pool,
+        user: user_account.user,
+        user_account: user_account.key(),
+    });
     
     Ok(())
 }
\ No newline at end of file
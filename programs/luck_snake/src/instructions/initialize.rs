@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
 use crate::state::*;
+use crate::events::ConfigInitialized;
 
 /// 初始化指令的账户结构
 /// 定义初始化程序所需的所有账户
@@ -24,18 +24,13 @@ pub struct Initialize<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     
-    /// 财库账户
-    /// 用于接收用户支付的费用
-    /// 可以是任何有效的系统账户
-    pub treasury: SystemAccount<'info>,
-    
     /// 系统程序
     /// 用于创建账户和转账等系统操作
     pub system_program: Program<'info, System>,
 }
 
 /// 初始化处理函数
-/// 设置程序的初始配置
+/// 设置程序的初始配置，数字池由管理员之后通过create_pool创建
 pub fn handler(ctx: Context<Initialize>) -> Result<()> {
     // 获取配置账户的可变引用
     let config = &mut ctx.accounts.config;
@@ -43,17 +38,27 @@ pub fn handler(ctx: Context<Initialize>) -> Result<()> {
     // 设置管理员地址为当前签名者
     config.authority = ctx.accounts.authority.key();
     
-    // 设置生成数字的价格（100,000 lamports）
-    config.generation_price = LuckSnakeConfig::GENERATION_PRICE;
+    // 存储PDA的bump值，用于后续验证
+    config.bump = ctx.bumps.config;
     
-    // 初始化已生成数字计数器为0
-    config.total_generated_numbers = 0;
+    // 预言机模式默认关闭，由管理员通过set_oracle启用
+    config.oracle_program = Pubkey::default();
+    config.oracle_authority = Pubkey::default();
     
-    // 设置财库地址
-    config.treasury = ctx.accounts.treasury.key();
+    // 没有进行中的管理员转移
+    config.pending_authority = Pubkey::default();
     
-    // 存储PDA的bump值，用于后续验证
-    config.bump = ctx.bumps.config;
+    // 生成默认开启，守护者由管理员通过set_guardian设置
+    config.paused = false;
+    config.guardian = Pubkey::default();
+    
+    // 默认不分账，由管理员通过set_fee_split设置
+    config.fee_recipients = [FeeRecipient::default(); LuckSnakeConfig::MAX_FEE_RECIPIENTS];
+    config.fee_accrued = [0; LuckSnakeConfig::MAX_FEE_RECIPIENTS];
+    
+    emit!(ConfigInitialized {
+        authority: config.authority,
+    });
     
     Ok(())
-}
\ No newline at end of file
+}
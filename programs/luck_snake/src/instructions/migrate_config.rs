@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::ConfigMigrated;
+
+/// 迁移配置账户指令的账户结构
+/// 旧版本的配置账户比当前结构短，无法按当前结构反序列化，因此使用原始账户
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// 程序配置账户
+    /// CHECK: 通过seeds验证地址，在处理函数中校验所有者和判别器
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump
+    )]
+    pub config: UncheckedAccount<'info>,
+    
+    /// 管理员账户
+    /// 需要签名，并支付扩容所需的额外租金
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 迁移配置账户处理函数
+/// 将旧配置账户扩容到当前大小，新增字段全部填零（即各字段的默认值）
+/// 账户已是当前大小时不做任何修改
+pub fn handler(ctx: Context<MigrateConfig>) -> Result<()> {
+    let config = ctx.accounts.config.to_account_info();
+    
+    // 校验账户确实是本程序的配置账户
+    require!(
+        config.owner == &crate::ID,
+        LuckSnakeError::InvalidConfigAccount
+    );
+    let current_len = config.data_len();
+    {
+        let data = config.try_borrow_data()?;
+        require!(
+            current_len >= 8 + 32 && data[..8] == *LuckSnakeConfig::DISCRIMINATOR,
+            LuckSnakeError::InvalidConfigAccount
+        );
+        
+        // 管理员字段位于判别器之后，所有版本的布局中位置相同
+        let stored_authority = Pubkey::try_from(&data[8..40]).unwrap();
+        require!(
+            stored_authority == ctx.accounts.authority.key(),
+            LuckSnakeError::Unauthorized
+        );
+    }
+    
+    if current_len >= LuckSnakeConfig::LEN {
+        emit!(ConfigMigrated {
+            authority: ctx.accounts.authority.key(),
+            old_len: current_len as u32,
+            new_len: current_len as u32,
+        });
+        return Ok(());
+    }
+    
+    // 补足扩容后所需的租金
+    let rent = Rent::get()?;
+    let additional_lamports = rent
+        .minimum_balance(LuckSnakeConfig::LEN)
+        .saturating_sub(config.lamports());
+    if additional_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: config.clone(),
+                },
+            ),
+            additional_lamports,
+        )?;
+    }
+    
+    // 扩容并将新增部分清零
+    config.resize(LuckSnakeConfig::LEN)?;
+    config.try_borrow_mut_data()?[current_len..].fill(0);
+    
+    emit!(ConfigMigrated {
+        authority: ctx.accounts.authority.key(),
+        old_len: current_len as u32,
+        new_len: LuckSnakeConfig::LEN as u32,
+    });
+    
+    Ok(())
+}
@@ -1,9 +1,90 @@
+// 每个指令模块都导出名为handler的处理函数，lib.rs通过完整路径调用
+#![allow(ambiguous_glob_reexports)]
+
 pub mod initialize;
+pub mod create_pool;
 pub mod init_user;
 pub mod generate_number;
+pub mod generate_numbers;
+pub mod claim_specific_number;
+pub mod register_number;
+pub mod transfer_number;
+pub mod tokenize_number;
 pub mod withdraw_funds;
+pub mod withdraw_token_funds;
+pub mod set_fee_split;
+pub mod distribute;
+pub mod distribute_tokens;
+pub mod set_withdrawal_recipient;
+pub mod set_payment_mint;
+pub mod clear_payment_mint;
+pub mod set_price_curve;
+pub mod quote_price;
+pub mod init_referral;
+pub mod set_referral_bps;
+pub mod claim_referral_rewards;
+pub mod commit_generation;
+pub mod reveal_generation;
+pub mod refund_generation;
+pub mod set_oracle;
+pub mod request_vrf_generation;
+pub mod fulfill_vrf_generation;
+pub mod complete_vrf_generation;
+pub mod cancel_vrf_generation;
+pub mod close_user_account;
+pub mod update_pool;
+pub mod propose_authority;
+pub mod accept_authority;
+pub mod cancel_authority_transfer;
+pub mod migrate_config;
+pub mod pause;
+pub mod unpause;
+pub mod set_guardian;
+pub mod open_round;
+pub mod request_draw;
+pub mod draw;
+pub mod claim_prize;
 
 pub use initialize::*;
+pub use create_pool::*;
 pub use init_user::*;
 pub use generate_number::*;
-pub use withdraw_funds::*;
\ No newline at end of file
+pub use generate_numbers::*;
+pub use claim_specific_number::*;
+pub use register_number::*;
+pub use transfer_number::*;
+pub use tokenize_number::*;
+pub use withdraw_funds::*;
+pub use withdraw_token_funds::*;
+pub use set_fee_split::*;
+pub use distribute::*;
+pub use distribute_tokens::*;
+pub use set_withdrawal_recipient::*;
+pub use set_payment_mint::*;
+pub use clear_payment_mint::*;
+pub use set_price_curve::*;
+pub use quote_price::*;
+pub use init_referral::*;
+pub use set_referral_bps::*;
+pub use claim_referral_rewards::*;
+pub use commit_generation::*;
+pub use reveal_generation::*;
+pub use refund_generation::*;
+pub use set_oracle::*;
+pub use request_vrf_generation::*;
+pub use fulfill_vrf_generation::*;
+pub use complete_vrf_generation::*;
+pub use cancel_vrf_generation::*;
+pub use close_user_account::*;
+pub use update_pool::*;
+pub use propose_authority::*;
+pub use accept_authority::*;
+pub use cancel_authority_transfer::*;
+pub use migrate_config::*;
+pub use pause::*;
+pub use unpause::*;
+pub use set_guardian::*;
+pub use open_round::*;
+pub use request_draw::*;
+pub use draw::*;
+pub use claim_prize::*;
\ No newline at end of file
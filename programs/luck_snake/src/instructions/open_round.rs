@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::RoundOpened;
+
+/// 开启轮次指令的账户结构
+#[derive(Accounts)]
+pub struct OpenRound<'info> {
+    /// 数字池账户
+    /// 只有数字池管理员可以开启轮次
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 轮次账户
+    /// 编号取自数字池的轮次数量，保证依次递增
+    #[account(
+        init,
+        payer = authority,
+        space = Round::LEN,
+        seeds = [Round::SEED, pool.key().as_ref(), &pool.round_count.to_le_bytes()],
+        bump
+    )]
+    pub round: Account<'info, Round>,
+    
+    /// 数字池管理员账户
+    /// 需要签名，支付轮次账户的租金
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 开启轮次处理函数
+/// 同一数字池同时只能有一个尚未开奖的轮次
+/// 开启之后收取的生成费用计入本轮奖池，开启之前的费用归数字池所有
+pub fn handler(ctx: Context<OpenRound>, close_time: i64) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    
+    require!(!pool.has_open_round(), LuckSnakeError::RoundInProgress);
+    require!(
+        close_time > Clock::get()?.unix_timestamp,
+        LuckSnakeError::InvalidRoundCloseTime
+    );
+    
+    let round = &mut ctx.accounts.round;
+    round.pool = pool.key();
+    round.round_id = pool.round_count;
+    round.close_time = close_time;
+    round.draw_slot = 0;
+    round.pot = 0;
+    round.winning_number = 0;
+    round.drawn = false;
+    round.bump = ctx.bumps.round;
+    // 开启时保存奖级表，本轮开奖按此计算，之后修改数字池的奖级只影响下一轮
+    round.prize_tiers = pool.prize_tiers;
+    
+    pool.round_count += 1;
+    pool.round_close_time = close_time;
+    
+    emit!(RoundOpened {
+        pool: pool.key(),
+        round: round.key(),
+        round_id: round.round_id,
+        close_time,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::GenerationPaused;
+
+/// 暂停生成指令的账户结构
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    /// 程序配置账户
+    /// 管理员或守护者可以暂停
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = signer.key() == config.authority
+            || (config.guardian != Pubkey::default() && signer.key() == config.guardian)
+            @ LuckSnakeError::Unauthorized
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 管理员或守护者账户
+    pub signer: Signer<'info>,
+}
+
+/// 暂停生成处理函数
+/// 暂停后拒绝所有生成数字的操作，提取资金、退款和关闭账户不受影响
+/// 记录暂停的slot，被暂停挡住揭示的承诺可以全额退款
+pub fn handler(ctx: Context<Pause>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    // 重复暂停时保留最早的暂停slot，保证记录的是整个暂停区间
+    if !config.paused {
+        config.paused = true;
+        config.paused_slot = Clock::get()?.slot;
+    }
+    
+    emit!(GenerationPaused {
+        paused_by: ctx.accounts.signer.key(),
+    });
+    
+    Ok(())
+}
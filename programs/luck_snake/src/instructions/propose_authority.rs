@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::AuthorityTransferProposed;
+
+/// 提议转移管理员指令的账户结构
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    /// 程序配置账户
+    /// 只有当前管理员可以提议
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 当前管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 提议转移管理员处理函数
+/// 只记录待接受的新管理员，新管理员签名接受之前当前管理员保持不变
+/// 再次提议会覆盖之前的待接受地址
+pub fn handler(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    
+    require!(
+        new_authority != Pubkey::default() && new_authority != config.authority,
+        LuckSnakeError::InvalidNewAuthority
+    );
+    
+    config.pending_authority = new_authority;
+    
+    emit!(AuthorityTransferProposed {
+        authority: config.authority,
+        pending_authority: new_authority,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+
+/// 查询价格指令的账户结构
+#[derive(Accounts)]
+pub struct QuotePrice<'info> {
+    /// 数字池账户
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+/// 查询价格处理函数
+/// 返回接下来生成count个数字的总价，返回值通过set_return_data写入交易结果
+pub fn handler(ctx: Context<QuotePrice>, count: u8) -> Result<u64> {
+    require!(
+        (1..=Pool::MAX_BATCH_COUNT).contains(&count),
+        LuckSnakeError::InvalidBatchCount
+    );
+    
+    let pool = &ctx.accounts.pool;
+    require!(
+        pool.total_generated_numbers + count as u32 <= pool.number_count(),
+        LuckSnakeError::AllNumbersGenerated
+    );
+    
+    pool.batch_price(count).ok_or(error!(LuckSnakeError::PriceOverflow))
+}
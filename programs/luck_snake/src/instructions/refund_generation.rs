@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::GenerationRefunded;
+
+/// 退还生成请求指令的账户结构
+#[derive(Accounts)]
+pub struct RefundGeneration<'info> {
+    /// 程序配置账户
+    /// 检查揭示窗口是否被暂停挡住
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    /// 不退还的费用计入财库收入
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = treasury
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 待揭示请求账户
+    /// 关闭后剩余的费用和租金退还给用户
+    #[account(
+        mut,
+        seeds = [PendingGeneration::SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump = pending.bump,
+        has_one = user,
+        close = user
+    )]
+    pub pending: Account<'info, PendingGeneration>,
+    
+    /// 财库账户
+    /// 接收不退还的费用
+    #[account(
+        mut,
+        seeds = [Treasury::SEED, pool.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    /// 用户钱包账户
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+/// 退还生成请求处理函数
+/// 当前无法揭示（生成已暂停或数字已全部发放）时随时全额退款
+/// 否则只有超过有效期的承诺才能退款：揭示窗口内有过暂停的全额退款，
+/// 用户自己放弃揭示的按EXPIRY_FORFEIT_BPS扣下费用转入财库，其余费用和请求账户的租金退还给用户
+pub fn handler(ctx: Context<RefundGeneration>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let pool = &ctx.accounts.pool;
+    let pending = &ctx.accounts.pending;
+    
+    let reveal_blocked = config.paused || pool.total_generated_numbers >= pool.number_count();
+    let forfeited = if reveal_blocked {
+        0
+    } else {
+        require!(
+            pending.is_expired(Clock::get()?.slot),
+            LuckSnakeError::CommitmentNotExpired
+        );
+        if config.was_paused_between(pending.reveal_slot() + 1, pending.expiry_slot()) {
+            0
+        } else {
+            pending.forfeited_amount()
+        }
+    };
+    
+    // 请求账户由本程序拥有，直接修改lamports完成转账
+    ctx.accounts.pending.sub_lamports(forfeited)?;
+    ctx.accounts.treasury.add_lamports(forfeited)?;
+    ctx.accounts.pool.record_revenue(forfeited, 0);
+    
+    emit!(GenerationRefunded {
+        pool: ctx.accounts.pool.key(),
+        user: ctx.accounts.pending.user,
+        refunded: ctx.accounts.pending.amount - forfeited,
+        forfeited,
+    });
+    
+    Ok(())
+}
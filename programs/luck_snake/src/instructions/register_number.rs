@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::NumberRegistered;
+use super::generate_number::create_number_record;
+
+/// 登记数字指令的账户结构
+#[derive(Accounts)]
+#[instruction(number: u32)]
+pub struct RegisterNumber<'info> {
+    /// 数字池账户
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 所有者的用户账户
+    /// 数字必须已经发放到该账户中
+    #[account(
+        seeds = [UserAccount::SEED, pool.key().as_ref(), owner.key().as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.numbers.contains(&number) @ LuckSnakeError::NumberNotInUserAccount
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    
+    /// 数字记录账户
+    /// CHECK: 通过seeds约束验证地址，由create_number_record创建并检查是否已存在
+    #[account(
+        mut,
+        seeds = [NumberRecord::SEED, pool.key().as_ref(), &number.to_le_bytes()],
+        bump
+    )]
+    pub number_record: UncheckedAccount<'info>,
+    
+    /// 数字所有者钱包账户
+    /// 需要签名，支付数字记录的租金
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 登记数字处理函数
+/// 随机生成时数字由链上随机值决定，客户端未能提供被选中数字的记录账户时，数字已经发放到用户账户，
+/// 由所有者之后调用本指令创建NumberRecord；转移和领奖以用户账户证明所有权，只有代币化需要数字记录
+pub fn handler(ctx: Context<RegisterNumber>, number: u32) -> Result<()> {
+    create_number_record(
+        &ctx.accounts.number_record.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.pool.key(),
+        number,
+        ctx.accounts.owner.key(),
+        ctx.bumps.number_record,
+    )?;
+    
+    emit!(NumberRegistered {
+        pool: ctx.accounts.pool.key(),
+        number,
+        owner: ctx.accounts.owner.key(),
+        number_record: ctx.accounts.number_record.key(),
+    });
+    
+    Ok(())
+}
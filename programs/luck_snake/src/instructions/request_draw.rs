@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::DrawRequested;
+
+/// 请求开奖指令的账户结构
+/// 任何人都可以在截止时间之后调用
+#[derive(Accounts)]
+pub struct RequestDraw<'info> {
+    /// 数字池账户
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 轮次账户
+    #[account(
+        mut,
+        seeds = [Round::SEED, pool.key().as_ref(), &round.round_id.to_le_bytes()],
+        bump = round.bump,
+        has_one = pool,
+        constraint = !round.drawn @ LuckSnakeError::RoundAlreadyDrawn
+    )]
+    pub round: Account<'info, Round>,
+}
+
+/// 请求开奖处理函数
+/// 记录一个未来的slot，开奖时使用该slot之后的区块哈希，请求时无法预知
+/// 每轮只能请求一次，否则不满意结果的参与者可以等请求过期后重新请求，换一个区块哈希
+pub fn handler(ctx: Context<RequestDraw>) -> Result<()> {
+    let clock = Clock::get()?;
+    let round = &mut ctx.accounts.round;
+    
+    require!(
+        clock.unix_timestamp >= round.close_time,
+        LuckSnakeError::RoundNotClosed
+    );
+    require!(!round.is_draw_requested(), LuckSnakeError::DrawAlreadyRequested);
+    
+    round.draw_slot = clock.slot + Round::DRAW_DELAY_SLOTS;
+    
+    emit!(DrawRequested {
+        pool: ctx.accounts.pool.key(),
+        round: round.key(),
+        round_id: round.round_id,
+        draw_slot: round.draw_slot,
+    });
+    
+    Ok(())
+}
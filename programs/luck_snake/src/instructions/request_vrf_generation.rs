@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::VrfRequested;
+use crate::oracle;
+use super::generate_number::check_max_price;
+
+/// 请求预言机随机数指令的账户结构
+#[derive(Accounts)]
+pub struct RequestVrfGeneration<'info> {
+    /// 程序配置账户
+    /// 提供预言机设置并检查全局暂停状态
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = !config.paused @ LuckSnakeError::GenerationPaused
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    /// 提供当前的生成价格
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 随机数请求账户
+    /// 每个用户在每个数字池中同时只能有一个未完成的请求
+    #[account(
+        init,
+        payer = user,
+        space = VrfRequest::LEN,
+        seeds = [VrfRequest::SEED, pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub vrf_request: Account<'info, VrfRequest>,
+    
+    /// 预言机一侧的请求账户
+    /// CHECK: 由预言机程序创建和校验
+    #[account(mut)]
+    pub oracle_request: UncheckedAccount<'info>,
+    
+    /// 预言机程序
+    /// CHECK: 通过constraint验证为配置中的预言机程序
+    #[account(
+        executable,
+        constraint = oracle_program.key() == config.oracle_program @ LuckSnakeError::OracleNotConfigured
+    )]
+    pub oracle_program: UncheckedAccount<'info>,
+    
+    /// 用户钱包账户
+    /// 需要签名，支付生成费用和请求账户的租金
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 请求预言机随机数处理函数
+/// 暂存生成费用并通过CPI向预言机发起请求
+pub fn handler(ctx: Context<RequestVrfGeneration>, max_price: Option<u64>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let pool = &ctx.accounts.pool;
+    
+    require!(
+        config.oracle_program != Pubkey::default(),
+        LuckSnakeError::OracleNotConfigured
+    );
+    
+    // 检查是否还有剩余数字可生成，避免用户为无法完成的请求付款
+    require!(
+        pool.total_generated_numbers < pool.number_count(),
+        LuckSnakeError::AllNumbersGenerated
+    );
+    
+    // 进行中的轮次截止之后不再接受请求，截止之前的请求在截止后仍无法完成时可全额取消
+    require!(
+        pool.is_accepting_numbers(Clock::get()?.unix_timestamp),
+        LuckSnakeError::RoundClosed
+    );
+    
+    // 费用以lamports暂存在请求账户中，代币支付的数字池只能直接生成
+    require!(!pool.pays_with_token(), LuckSnakeError::TokenPaymentNotSupported);
+    
+    let price = pool.current_price().ok_or(LuckSnakeError::PriceOverflow)?;
+    check_max_price(price, max_price)?;
+    
+    // 将生成费用转入请求账户暂存，完成生成时再转入财库
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.vrf_request.to_account_info(),
+            },
+        ),
+        price,
+    )?;
+    
+    let clock = Clock::get()?;
+    let user_key = ctx.accounts.user.key();
+    let pool_key = pool.key();
+    
+    let vrf_request = &mut ctx.accounts.vrf_request;
+    vrf_request.pool = pool_key;
+    vrf_request.user = user_key;
+    vrf_request.amount = price;
+    vrf_request.request_slot = clock.slot;
+    vrf_request.randomness = [0u8; 32];
+    vrf_request.fulfilled = false;
+    vrf_request.bump = ctx.bumps.vrf_request;
+    
+    // 请求种子仅用于区分请求，随机性由预言机提供
+    let seed = anchor_lang::solana_program::keccak::hashv(&[
+        user_key.as_ref(),
+        &clock.slot.to_le_bytes(),
+    ])
+    .to_bytes();
+    
+    let bump = [ctx.bumps.vrf_request];
+    let signer_seeds: &[&[&[u8]]] = &[&[VrfRequest::SEED, pool_key.as_ref(), user_key.as_ref(), &bump]];
+    oracle::request_randomness(
+        &ctx.accounts.oracle_program.to_account_info(),
+        &ctx.accounts.vrf_request.to_account_info(),
+        &ctx.accounts.oracle_request.to_account_info(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        seed,
+        signer_seeds,
+    )?;
+    
+    emit!(VrfRequested {
+        pool: pool_key,
+        user: user_key,
+        amount: price,
+        request_slot: clock.slot,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::FeeSplitUpdated;
+
+/// 设置分账表指令的账户结构
+#[derive(Accounts)]
+pub struct SetFeeSplit<'info> {
+    /// 程序配置账户
+    /// 只有管理员可以设置分账表
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 校验分账表并补齐为固定长度
+/// 接收方地址不能为默认值且互不相同，份额不能为0，份额之和必须为10,000；空表表示取消分账
+fn validate_fee_recipients(
+    fee_recipients: &[FeeRecipient],
+) -> Result<[FeeRecipient; LuckSnakeConfig::MAX_FEE_RECIPIENTS]> {
+    require!(
+        fee_recipients.len() <= LuckSnakeConfig::MAX_FEE_RECIPIENTS,
+        LuckSnakeError::InvalidFeeSplit
+    );
+    
+    let mut validated = [FeeRecipient::default(); LuckSnakeConfig::MAX_FEE_RECIPIENTS];
+    let mut total_share_bps = 0u64;
+    for (index, entry) in fee_recipients.iter().enumerate() {
+        require!(
+            entry.recipient != Pubkey::default()
+                && entry.share_bps > 0
+                && !fee_recipients[..index].iter().any(|other| other.recipient == entry.recipient),
+            LuckSnakeError::InvalidFeeSplit
+        );
+        total_share_bps += entry.share_bps as u64;
+        validated[index] = *entry;
+    }
+    require!(
+        fee_recipients.is_empty() || total_share_bps == Pool::BPS_DENOMINATOR,
+        LuckSnakeError::InvalidFeeSplit
+    );
+    
+    Ok(validated)
+}
+
+/// 设置分账表处理函数
+/// 还有已分得但尚未支付的lamports或代币时不能修改或取消分账表，需先通过distribute和distribute_tokens全部支付
+pub fn handler(ctx: Context<SetFeeSplit>, fee_recipients: Vec<FeeRecipient>) -> Result<()> {
+    let new_fee_recipients = validate_fee_recipients(&fee_recipients)?;
+    
+    let config = &mut ctx.accounts.config;
+    require!(
+        !config.has_undistributed_fees(),
+        LuckSnakeError::UndistributedFees
+    );
+    
+    let old_fee_recipients = config.fee_recipients;
+    config.fee_recipients = new_fee_recipients;
+    
+    emit!(FeeSplitUpdated {
+        authority: config.authority,
+        old_fee_recipients,
+        new_fee_recipients,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::GuardianUpdated;
+
+/// 设置守护者指令的账户结构
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    /// 程序配置账户
+    /// 只有管理员可以设置守护者
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 设置守护者处理函数
+/// 将守护者设置为默认地址即可移除守护者
+pub fn handler(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let old_guardian = config.guardian;
+    config.guardian = guardian;
+    
+    emit!(GuardianUpdated {
+        authority: config.authority,
+        old_guardian,
+        new_guardian: guardian,
+    });
+    
+    Ok(())
+}
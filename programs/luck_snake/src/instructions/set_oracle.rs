@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::events::OracleUpdated;
+
+/// 设置预言机指令的账户结构
+#[derive(Accounts)]
+pub struct SetOracle<'info> {
+    /// 程序配置账户
+    /// 只有管理员可以修改预言机设置
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 设置预言机处理函数
+/// 将预言机程序设置为默认地址即可关闭预言机模式
+pub fn handler(ctx: Context<SetOracle>, oracle_program: Pubkey, oracle_authority: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let old_oracle_program = config.oracle_program;
+    let old_oracle_authority = config.oracle_authority;
+    config.oracle_program = oracle_program;
+    config.oracle_authority = oracle_authority;
+    
+    emit!(OracleUpdated {
+        authority: ctx.accounts.authority.key(),
+        old_oracle_program,
+        new_oracle_program: oracle_program,
+        old_oracle_authority,
+        new_oracle_authority: oracle_authority,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::PaymentMintUpdated;
+use super::generate_number::check_payment_mint_extensions;
+
+/// 设置支付代币指令的账户结构
+#[derive(Accounts)]
+pub struct SetPaymentMint<'info> {
+    /// 数字池账户
+    /// 只有数字池管理员可以设置支付代币
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 支付代币的mint账户
+    /// 可以是SPL Token或Token-2022的mint
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    
+    /// 财库代币账户
+    /// 每种支付代币对应一个由数字池控制的PDA代币账户，切换回以前使用过的代币时沿用原账户
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [Pool::TREASURY_TOKEN_SEED, pool.key().as_ref(), payment_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = pool,
+        token::token_program = token_program
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// 数字池管理员账户
+    /// 需要签名，支付财库代币账户的租金
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// mint所属的SPL Token或Token-2022程序
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 设置支付代币处理函数
+/// 之后生成数字按代币价格从用户的代币账户收取费用
+pub fn handler(ctx: Context<SetPaymentMint>, token_price: u64) -> Result<()> {
+    require!(token_price > 0, LuckSnakeError::InvalidTokenPrice);
+    check_payment_mint_extensions(&ctx.accounts.payment_mint.to_account_info())?;
+    
+    let pool = &mut ctx.accounts.pool;
+    let old_payment_mint = pool.payment_mint;
+    pool.payment_mint = ctx.accounts.payment_mint.key();
+    pool.token_price = token_price;
+    
+    emit!(PaymentMintUpdated {
+        pool: pool.key(),
+        old_payment_mint,
+        new_payment_mint: pool.payment_mint,
+        token_price,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::PriceCurveUpdated;
+
+/// 设置价格曲线指令的账户结构
+#[derive(Accounts)]
+pub struct SetPriceCurve<'info> {
+    /// 数字池账户
+    /// 只有数字池管理员可以设置价格曲线
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 数字池管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 设置价格曲线处理函数
+/// 起始价格仍为生成价格（以代币支付时为代币价格），范围内最后一个数字的价格必须能够计算
+pub fn handler(ctx: Context<SetPriceCurve>, price_curve: PriceCurve, price_curve_slope: u64) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    
+    require!(
+        price_curve != PriceCurve::Exponential || price_curve_slope <= Pool::MAX_EXPONENTIAL_SLOPE_BPS,
+        LuckSnakeError::InvalidPriceCurve
+    );
+    
+    let old_price_curve = pool.price_curve;
+    let old_price_curve_slope = pool.price_curve_slope;
+    pool.price_curve = price_curve;
+    pool.price_curve_slope = price_curve_slope;
+    
+    require!(
+        pool.price_at(pool.number_count() - 1).is_some(),
+        LuckSnakeError::InvalidPriceCurve
+    );
+    
+    emit!(PriceCurveUpdated {
+        pool: pool.key(),
+        old_price_curve,
+        old_price_curve_slope,
+        new_price_curve: price_curve,
+        new_price_curve_slope: price_curve_slope,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::ReferralBpsUpdated;
+
+/// 设置推荐比例指令的账户结构
+#[derive(Accounts)]
+pub struct SetReferralBps<'info> {
+    /// 数字池账户
+    /// 只有数字池管理员可以设置推荐比例
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 数字池管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 设置推荐比例处理函数
+/// 比例以基点表示，不能超过MAX_REFERRAL_BPS，设为0时不再发放推荐奖励
+pub fn handler(ctx: Context<SetReferralBps>, referral_bps: u16) -> Result<()> {
+    require!(
+        referral_bps <= Pool::MAX_REFERRAL_BPS,
+        LuckSnakeError::InvalidReferralShare
+    );
+    
+    let pool = &mut ctx.accounts.pool;
+    let old_referral_bps = pool.referral_bps;
+    pool.referral_bps = referral_bps;
+    
+    emit!(ReferralBpsUpdated {
+        pool: pool.key(),
+        old_referral_bps,
+        new_referral_bps: referral_bps,
+    });
+    
+    Ok(())
+}
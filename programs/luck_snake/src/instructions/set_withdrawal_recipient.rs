@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::WithdrawalRecipientUpdated;
+
+/// 设置固定接收方指令的账户结构
+#[derive(Accounts)]
+pub struct SetWithdrawalRecipient<'info> {
+    /// 程序配置账户
+    /// 只有程序管理员可以设置，数字池管理员只能向设置的接收方提取
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 程序管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 设置固定接收方处理函数
+/// 设置后withdraw_funds和withdraw_token_funds只能提取到该地址（代币提取到其拥有的代币账户），
+/// 更换接收方即完成轮换；默认值表示取消限制，由数字池管理员在每次提取时指定接收方
+/// 接收方不能是数字池或其财库，避免资金转回程序账户
+pub fn handler(ctx: Context<SetWithdrawalRecipient>, recipient: Pubkey) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    require!(
+        recipient != pool.key() && recipient != pool.treasury,
+        LuckSnakeError::InvalidWithdrawalRecipient
+    );
+    
+    let old_recipient = pool.withdrawal_recipient;
+    pool.withdrawal_recipient = recipient;
+    
+    emit!(WithdrawalRecipientUpdated {
+        pool: pool.key(),
+        authority: ctx.accounts.authority.key(),
+        old_recipient,
+        new_recipient: recipient,
+    });
+    
+    Ok(())
+}
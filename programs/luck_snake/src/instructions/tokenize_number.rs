@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, spl_token::instruction::AuthorityType, Mint, MintTo, SetAuthority, Token, TokenAccount};
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::NumberTokenized;
+
+/// 代币化数字指令的账户结构
+#[derive(Accounts)]
+#[instruction(number: u32)]
+pub struct TokenizeNumber<'info> {
+    /// 数字池账户
+    /// 作为mint的铸造权限签名，铸造完成后撤销
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 数字记录账户
+    /// 只有当前所有者可以代币化，之后owner记为mint地址
+    #[account(
+        mut,
+        seeds = [NumberRecord::SEED, pool.key().as_ref(), &number.to_le_bytes()],
+        bump = number_record.bump,
+        constraint = number_record.owner == owner.key() @ LuckSnakeError::Unauthorized
+    )]
+    pub number_record: Account<'info, NumberRecord>,
+    
+    /// 所有者的用户账户
+    /// 数字移出后缩小一个数字的空间，多余的租金退还给所有者
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED, pool.key().as_ref(), owner.key().as_ref()],
+        bump = user_account.bump,
+        realloc = user_account.space_after_removing_number(),
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    
+    /// 数字的mint账户
+    /// 由数字派生的PDA，0位小数，不设冻结权限
+    #[account(
+        init,
+        payer = owner,
+        seeds = [NumberToken::MINT_SEED, pool.key().as_ref(), &number.to_le_bytes()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = pool
+    )]
+    pub mint: Account<'info, Mint>,
+    
+    /// 所有者的关联代币账户
+    /// 接收铸造的唯一一枚代币
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    
+    /// 数字代币信息账户
+    /// 保存代币对应的数字和生成时间
+    #[account(
+        init,
+        payer = owner,
+        space = NumberToken::LEN,
+        seeds = [NumberToken::SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub number_token: Account<'info, NumberToken>,
+    
+    /// 数字所有者钱包账户
+    /// 需要签名，支付新账户的租金
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    /// SPL Token程序
+    pub token_program: Program<'info, Token>,
+    
+    /// 关联代币账户程序
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 代币化数字处理函数
+/// 铸造一枚代币后撤销铸造权限，保证供应量固定为1
+pub fn handler(ctx: Context<TokenizeNumber>, number: u32) -> Result<()> {
+    // 数字从用户账户移出，所有权改由代币表示，账户空间已由realloc约束缩小
+    let user_account = &mut ctx.accounts.user_account;
+    let position = user_account
+        .numbers
+        .iter()
+        .position(|owned| *owned == number)
+        .ok_or(LuckSnakeError::NumberNotInUserAccount)?;
+    user_account.numbers.remove(position);
+    
+    let pool = &ctx.accounts.pool;
+    let pool_id_bytes = pool.pool_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[Pool::SEED, &pool_id_bytes, &[pool.bump]]];
+    
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.token_account.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )?;
+    
+    token::set_authority(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: pool.to_account_info(),
+                account_or_mint: ctx.accounts.mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+    
+    let number_record = &mut ctx.accounts.number_record;
+    number_record.owner = ctx.accounts.mint.key();
+    
+    let number_token = &mut ctx.accounts.number_token;
+    number_token.pool = pool.key();
+    number_token.mint = ctx.accounts.mint.key();
+    number_token.number = number;
+    number_token.generated_at = number_record.generated_at;
+    number_token.bump = ctx.bumps.number_token;
+    
+    emit!(NumberTokenized {
+        pool: pool.key(),
+        number,
+        mint: ctx.accounts.mint.key(),
+        owner: ctx.accounts.owner.key(),
+    });
+    
+    Ok(())
+}
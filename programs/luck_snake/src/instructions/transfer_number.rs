@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::{NumberTransferred, UserInitialized};
+use super::generate_number::number_record_exists;
+
+/// 转移数字指令的账户结构
+#[derive(Accounts)]
+#[instruction(number: u32, recipient: Pubkey)]
+pub struct TransferNumber<'info> {
+    /// 数字池账户
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 被转移数字的记录账户
+    /// 可能尚未创建，已创建时同时更新所有者
+    /// CHECK: 通过seeds约束验证地址，在处理函数中检查是否已创建
+    #[account(
+        mut,
+        seeds = [NumberRecord::SEED, pool.key().as_ref(), &number.to_le_bytes()],
+        bump
+    )]
+    pub number_record: UncheckedAccount<'info>,
+    
+    /// 发送方的用户账户
+    /// 缩小一个数字的空间，多余的租金退还给发送方
+    #[account(
+        mut,
+        seeds = [UserAccount::SEED, pool.key().as_ref(), sender.key().as_ref()],
+        bump = sender_account.bump,
+        realloc = sender_account.space_after_removing_number(),
+        realloc::payer = sender,
+        realloc::zero = false
+    )]
+    pub sender_account: Account<'info, UserAccount>,
+    
+    /// 接收方的用户账户
+    /// 不存在时由发送方支付租金创建，之后在处理函数中扩容一个数字的空间
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = UserAccount::BASE_LEN,
+        seeds = [UserAccount::SEED, pool.key().as_ref(), recipient.as_ref()],
+        bump
+    )]
+    pub recipient_account: Account<'info, UserAccount>,
+    
+    /// 发送方钱包账户
+    /// 需要签名，支付接收方账户的租金
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 转移数字处理函数
+/// 把数字从发送方的用户账户移到接收方的用户账户，数字记录已创建时同时更新其所有者
+/// 只有用户账户中持有该数字的发送方可以转移，已代币化的数字不在用户账户中
+/// 接收方不能是发送方自己或默认地址，转给默认地址的数字无人能够取回
+pub fn handler(ctx: Context<TransferNumber>, number: u32, recipient: Pubkey) -> Result<()> {
+    require!(
+        recipient != ctx.accounts.sender.key() && recipient != Pubkey::default(),
+        LuckSnakeError::InvalidTransferRecipient
+    );
+    
+    // 从发送方的数字列表中移除，账户空间已由realloc约束缩小
+    let sender_account = &mut ctx.accounts.sender_account;
+    let position = sender_account
+        .numbers
+        .iter()
+        .position(|owned| *owned == number)
+        .ok_or(LuckSnakeError::Unauthorized)?;
+    sender_account.numbers.remove(position);
+    
+    // 接收方账户刚创建时写入基础字段
+    let recipient_account = &mut ctx.accounts.recipient_account;
+    if recipient_account.user == Pubkey::default() {
+        recipient_account.pool = ctx.accounts.pool.key();
+        recipient_account.user = recipient;
+        recipient_account.numbers = Vec::new();
+        recipient_account.nonce = 0;
+        recipient_account.bump = ctx.bumps.recipient_account;
+        
+        emit!(UserInitialized {
+            pool: recipient_account.pool,
+            user: recipient,
+            user_account: recipient_account.key(),
+        });
+    }
+    
+    // 接收方账户扩容一个数字的空间，新增租金由发送方支付
+    let recipient_info = recipient_account.to_account_info();
+    let new_len = recipient_account.space_after_adding_number();
+    let additional_lamports = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(recipient_info.lamports());
+    if additional_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.sender.to_account_info(),
+                    to: recipient_info.clone(),
+                },
+            ),
+            additional_lamports,
+        )?;
+    }
+    recipient_info.resize(new_len)?;
+    recipient_account.numbers.push(number);
+    
+    // 数字记录与用户账户保持一致，未创建的记录之后登记时直接写入接收方
+    let number_record = ctx.accounts.number_record.to_account_info();
+    if number_record_exists(&number_record) {
+        let mut data = number_record.try_borrow_mut_data()?;
+        let mut record = NumberRecord::try_deserialize(&mut &data[..])?;
+        require!(
+            record.owner == ctx.accounts.sender.key(),
+            LuckSnakeError::Unauthorized
+        );
+        record.owner = recipient;
+        record.try_serialize(&mut &mut data[..])?;
+    }
+    
+    emit!(NumberTransferred {
+        pool: ctx.accounts.pool.key(),
+        number,
+        from: ctx.accounts.sender.key(),
+        to: recipient,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::GenerationUnpaused;
+
+/// 恢复生成指令的账户结构
+#[derive(Accounts)]
+pub struct Unpause<'info> {
+    /// 程序配置账户
+    /// 只有管理员可以恢复，守护者只能暂停
+    #[account(
+        mut,
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 恢复生成处理函数
+/// 记录恢复的slot，与暂停slot一起判断承诺的揭示窗口是否被暂停挡住
+pub fn handler(ctx: Context<Unpause>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    if config.paused {
+        config.paused = false;
+        config.unpaused_slot = Clock::get()?.slot;
+    }
+    
+    emit!(GenerationUnpaused {
+        authority: ctx.accounts.authority.key(),
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::PoolUpdated;
+
+/// 更新数字池指令的账户结构
+#[derive(Accounts)]
+pub struct UpdatePool<'info> {
+    /// 数字池账户
+    /// 只有数字池管理员可以修改
+    #[account(
+        mut,
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 数字池管理员账户
+    pub authority: Signer<'info>,
+}
+
+/// 校验奖级表并补齐为固定长度
+/// 每个奖级的匹配位数必须在1到MAX_MATCH_DIGITS之间且互不相同，份额必须大于0且之和不能超过整个奖池
+/// 份额为0的奖级会让命中它的数字不再计入匹配位数更少、有份额的奖级
+fn validate_prize_tiers(prize_tiers: &[PrizeTier]) -> Result<[PrizeTier; Pool::MAX_PRIZE_TIERS]> {
+    require!(
+        prize_tiers.len() <= Pool::MAX_PRIZE_TIERS,
+        LuckSnakeError::InvalidPrizeTiers
+    );
+    
+    let mut validated = [PrizeTier::default(); Pool::MAX_PRIZE_TIERS];
+    let mut total_share_bps = 0u64;
+    for (index, tier) in prize_tiers.iter().enumerate() {
+        require!(
+            (1..=Pool::MAX_MATCH_DIGITS).contains(&tier.match_digits)
+                && !prize_tiers[..index].iter().any(|other| other.match_digits == tier.match_digits),
+            LuckSnakeError::InvalidPrizeTiers
+        );
+        require!(tier.share_bps > 0, LuckSnakeError::InvalidPrizeShare);
+        total_share_bps += tier.share_bps as u64;
+        validated[index] = *tier;
+    }
+    require!(
+        total_share_bps <= Pool::BPS_DENOMINATOR,
+        LuckSnakeError::InvalidPrizeShare
+    );
+    
+    Ok(validated)
+}
+
+/// 更新数字池处理函数
+/// 未提供的参数保持不变
+pub fn handler(
+    ctx: Context<UpdatePool>,
+    generation_price: Option<u64>,
+    prize_tiers: Option<Vec<PrizeTier>>,
+    specific_number_premium_bps: Option<u32>,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let old_generation_price = pool.generation_price;
+    let old_prize_tiers = pool.prize_tiers;
+    let old_specific_number_premium_bps = pool.specific_number_premium_bps;
+    
+    if let Some(generation_price) = generation_price {
+        // 价格必须在合理范围内，防止误操作设置为0或极高的价格
+        require!(
+            (Pool::MIN_GENERATION_PRICE..=Pool::MAX_GENERATION_PRICE).contains(&generation_price),
+            LuckSnakeError::InvalidGenerationPrice
+        );
+        pool.generation_price = generation_price;
+    }
+    
+    if let Some(prize_tiers) = prize_tiers {
+        pool.prize_tiers = validate_prize_tiers(&prize_tiers)?;
+    }
+    
+    if let Some(specific_number_premium_bps) = specific_number_premium_bps {
+        // 指定数字不能比随机生成便宜
+        require!(
+            (Pool::BPS_DENOMINATOR as u32..=Pool::MAX_SPECIFIC_NUMBER_PREMIUM_BPS)
+                .contains(&specific_number_premium_bps),
+            LuckSnakeError::InvalidSpecificNumberPremium
+        );
+        pool.specific_number_premium_bps = specific_number_premium_bps;
+    }
+    
+    emit!(PoolUpdated {
+        pool: pool.key(),
+        authority: ctx.accounts.authority.key(),
+        old_generation_price,
+        new_generation_price: pool.generation_price,
+        old_prize_tiers,
+        new_prize_tiers: pool.prize_tiers,
+        old_specific_number_premium_bps,
+        new_specific_number_premium_bps: pool.specific_number_premium_bps,
+    });
+    
+    Ok(())
+}
@@ -1,77 +1,109 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
 use crate::state::*;
 use crate::errors::LuckSnakeError;
+use crate::events::FundsWithdrawn;
 
 /// 提取资金指令的账户结构
 /// 定义提取资金所需的所有账户
 #[derive(Accounts)]
 pub struct WithdrawFunds<'info> {
     /// 程序配置账户
-    /// 验证管理员和财库地址
+    /// 设置了分账表时拒绝提取
     #[account(
-        seeds = [LuckSnakeConfig::SEED],        // 使用PDA种子验证
-        bump,                                    // 验证bump值
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = !config.has_fee_split() @ LuckSnakeError::FeeSplitActive
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    /// 验证数字池管理员和财库地址
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()], // 使用PDA种子验证
+        bump = pool.bump,                       // 验证bump值
         has_one = authority,                    // 验证管理员地址匹配
         has_one = treasury                      // 验证财库地址匹配
     )]
-    pub config: Account<'info, LuckSnakeConfig>,
+    pub pool: Account<'info, Pool>,
     
-    /// 管理员账户
-    /// 必须是程序配置中设置的管理员
+    /// 数字池管理员账户
+    /// 必须是数字池中设置的管理员
     /// 需要签名授权
     #[account(mut)]
     pub authority: Signer<'info>,
     
     /// 财库账户
-    /// 存储所有用户支付的费用
-    /// 资金将从这里转出
-    #[account(mut, signer)]
-    pub treasury: SystemAccount<'info>,
+    /// 由本程序拥有的PDA，存储本池用户支付的费用
+    /// 资金将从这里转出，无需财库签名
+    #[account(
+        mut,
+        seeds = [Treasury::SEED, pool.key().as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    /// 提取日志账户
+    /// 每次提取都会追加一条记录
+    #[account(
+        mut,
+        seeds = [WithdrawalLog::SEED, pool.key().as_ref()],
+        bump = withdrawal_log.load()?.bump
+    )]
+    pub withdrawal_log: AccountLoader<'info, WithdrawalLog>,
     
     /// 接收方账户
-    /// 接收提取的资金
-    #[account(mut)]
+    /// 接收提取的资金，数字池设置了固定接收方时必须是该地址
+    #[account(
+        mut,
+        constraint = pool.accepts_withdrawal_to(&recipient.key()) @ LuckSnakeError::InvalidWithdrawalRecipient
+    )]
     pub recipient: SystemAccount<'info>,
-    
-    /// 系统程序
-    /// 用于转账操作
-    pub system_program: Program<'info, System>,
 }
 
 /// 提取资金处理函数
-/// 将财库中的所有资金转移到接收方账户
-pub fn handler(ctx: Context<WithdrawFunds>) -> Result<()> {
-    // 获取财库当前余额
-    let treasury_balance = ctx.accounts.treasury.lamports();
+/// 将指定金额从财库转移到接收方账户，财库余额不能低于免租金最低余额
+pub fn handler(ctx: Context<WithdrawFunds>, amount: u64) -> Result<()> {
+    require!(amount > 0, LuckSnakeError::InvalidWithdrawalAmount);
+    
+    // 财库需要保留免租金最低余额，避免账户被回收
+    let treasury_info = ctx.accounts.treasury.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(treasury_info.data_len());
+    // 已开奖未领取的奖金和进行中轮次的奖池同样不能提取
+    let withdrawable = treasury_info
+        .lamports()
+        .saturating_sub(rent_exempt_minimum)
+        .saturating_sub(ctx.accounts.pool.locked_funds());
     
     // 检查财库是否有资金可提取
     require!(
-        treasury_balance > 0,
+        withdrawable > 0,
         LuckSnakeError::NoFundsToWithdraw
     );
-    
-    // 创建转账指令，从财库转账到接收方
-    let transfer_instruction = system_program::Transfer {
-        from: ctx.accounts.treasury.to_account_info(),
-        to: ctx.accounts.recipient.to_account_info(),
-    };
-    
-    // 创建CPI上下文
-    let transfer_context = CpiContext::new(
-        ctx.accounts.system_program.to_account_info(),
-        transfer_instruction,
+    require!(
+        amount <= withdrawable,
+        LuckSnakeError::InsufficientTreasuryFunds
     );
     
-    // 执行转账
-    system_program::transfer(transfer_context, treasury_balance)?;
+    // 财库由本程序拥有，直接修改lamports完成转账
+    ctx.accounts.treasury.sub_lamports(amount)?;
+    ctx.accounts.recipient.add_lamports(amount)?;
     
-    // 记录提取日志
-    msg!(
-        "提取 {} lamports 到地址 {}",
-        treasury_balance,
-        ctx.accounts.recipient.key()
-    );
+    // 写入链上提取日志，便于对账
+    let mut withdrawal_log = ctx.accounts.withdrawal_log.load_mut()?;
+    withdrawal_log.push(WithdrawalRecord {
+        timestamp: Clock::get()?.unix_timestamp,
+        amount,
+        recipient: ctx.accounts.recipient.key(),
+    });
+    
+    emit!(FundsWithdrawn {
+        pool: ctx.accounts.pool.key(),
+        authority: ctx.accounts.authority.key(),
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+        treasury_balance: ctx.accounts.treasury.get_lamports(),
+        total_withdrawals: withdrawal_log.total_withdrawals,
+    });
     
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use crate::state::*;
+use crate::errors::LuckSnakeError;
+use crate::events::TokenFundsWithdrawn;
+
+/// 提取代币资金指令的账户结构
+#[derive(Accounts)]
+pub struct WithdrawTokenFunds<'info> {
+    /// 程序配置账户
+    /// 设置了分账表时拒绝提取
+    #[account(
+        seeds = [LuckSnakeConfig::SEED],
+        bump,
+        constraint = !config.has_fee_split() @ LuckSnakeError::FeeSplitActive
+    )]
+    pub config: Account<'info, LuckSnakeConfig>,
+    
+    /// 数字池账户
+    /// 验证数字池管理员，并作为财库代币账户的权限签名
+    #[account(
+        seeds = [Pool::SEED, &pool.pool_id.to_le_bytes()],
+        bump = pool.bump,
+        has_one = authority @ LuckSnakeError::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    /// 代币的mint账户
+    /// 可以是当前或以前使用过的支付代币
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    
+    /// 财库代币账户
+    #[account(
+        mut,
+        seeds = [Pool::TREASURY_TOKEN_SEED, pool.key().as_ref(), payment_mint.key().as_ref()],
+        bump
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// 接收方代币账户
+    /// 数字池设置了固定接收方时必须归该地址所有
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::token_program = token_program,
+        constraint = pool.accepts_withdrawal_to(&recipient_token_account.owner) @ LuckSnakeError::InvalidWithdrawalRecipient
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// 数字池管理员账户
+    pub authority: Signer<'info>,
+    
+    /// mint所属的SPL Token或Token-2022程序
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// 提取代币资金处理函数
+/// 将指定数量的代币从财库代币账户转到接收方代币账户
+/// mint带有转账手续费时，接收方收到的是扣除手续费之后的数量
+pub fn handler(ctx: Context<WithdrawTokenFunds>, amount: u64) -> Result<()> {
+    require!(amount > 0, LuckSnakeError::InvalidWithdrawalAmount);
+    require!(
+        ctx.accounts.treasury_token_account.amount > 0,
+        LuckSnakeError::NoFundsToWithdraw
+    );
+    require!(
+        amount <= ctx.accounts.treasury_token_account.amount,
+        LuckSnakeError::InsufficientTreasuryFunds
+    );
+    
+    let pool = &ctx.accounts.pool;
+    let pool_id_bytes = pool.pool_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[Pool::SEED, &pool_id_bytes, &[pool.bump]]];
+    
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.treasury_token_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+    
+    ctx.accounts.treasury_token_account.reload()?;
+    
+    emit!(TokenFundsWithdrawn {
+        pool: ctx.accounts.pool.key(),
+        authority: ctx.accounts.authority.key(),
+        mint: ctx.accounts.payment_mint.key(),
+        recipient: ctx.accounts.recipient_token_account.key(),
+        amount,
+        treasury_balance: ctx.accounts.treasury_token_account.amount,
+    });
+    
+    Ok(())
+}
@@ -1,40 +1,360 @@
+// Anchor 0.31.1在#[program]宏展开的IDL指令中仍调用已弃用的AccountInfo::realloc
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 
 // 引入模块
 pub mod errors;      // 错误定义模块
+pub mod events;      // 事件定义模块
 pub mod instructions; // 指令处理模块
+pub mod randomness;   // 随机数工具模块
+pub mod oracle;       // 预言机接口模块
 pub mod state;       // 状态/账户结构模块
 
 use instructions::*;
+use state::{FeeRecipient, PriceCurve, PrizeTier};
 
 // 程序ID - 这是部署后程序的唯一标识符
 // 在实际部署时需要更新为真实的程序ID
 declare_id!("F4gich1NV3oAT7UFbqQP5ERr8Sk7zUqebsbmsHffiBp1");
 
 /// LuckSnake主程序
-/// 实现了一个随机数字生成系统，用户支付费用后可以获得0-999范围内的唯一随机数
+/// 实现了一个随机数字生成系统，用户支付费用后可以在数字池的范围内获得唯一随机数
 #[program]
 pub mod luck_snake {
     use super::*;
 
     /// 初始化程序
-    /// 设置程序的管理员和财库地址
+    /// 设置程序的管理员
     /// 只需要在程序首次部署后调用一次
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         instructions::initialize::handler(ctx)
     }
 
+    /// 创建数字池
+    /// 只有程序管理员可以调用，每个数字池有独立的管理员、价格、数字范围[range_min, range_max]和财库
+    pub fn create_pool(
+        ctx: Context<CreatePool>,
+        pool_id: u64,
+        pool_authority: Pubkey,
+        generation_price: u64,
+        range_min: u32,
+        range_max: u32,
+    ) -> Result<()> {
+        instructions::create_pool::handler(
+            ctx,
+            pool_id,
+            pool_authority,
+            generation_price,
+            range_min,
+            range_max,
+        )
+    }
+
+    /// 初始化用户账户
+    /// 用户在某个数字池中生成数字之前需要先创建自己在该池的用户账户
+    pub fn init_user(ctx: Context<InitUser>) -> Result<()> {
+        instructions::init_user::handler(ctx)
+    }
+
+    /// 关闭用户账户
+    /// 用户不再持有任何数字后，可以关闭账户取回租金
+    pub fn close_user_account(ctx: Context<CloseUserAccount>) -> Result<()> {
+        instructions::close_user_account::handler(ctx)
+    }
+
     /// 生成随机数字
-    /// 用户支付100,000 lamports（约0.0001 SOL）来生成一个唯一的随机数字
-    /// 数字范围是0-999，每个用户获得的数字保证不重复
-    pub fn generate_number(ctx: Context<GenerateNumber>) -> Result<()> {
-        instructions::generate_number::handler(ctx)
+    /// 用户按数字池价格曲线的当前价格支付费用来生成一个唯一的随机数字
+    /// 数字在数字池的范围内，同一池中每个数字只会发放一次
+    /// 可以通过remaining_accounts提供可能被选中数字的NumberRecord账户，未提供被选中数字的记录时之后通过register_number创建
+    /// max_price为愿意支付的最高价格，代币带转账手续费时包含手续费，None表示不限制
+    pub fn generate_number<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GenerateNumber<'info>>,
+        max_price: Option<u64>,
+    ) -> Result<()> {
+        instructions::generate_number::handler(ctx, max_price)
+    }
+
+    /// 批量生成随机数字
+    /// 一次收取count个数字的费用并发放count个唯一数字，count不能超过Pool::MAX_BATCH_COUNT
+    /// 可以通过remaining_accounts提供可能被选中数字的NumberRecord账户，未提供的记录之后通过register_number创建
+    /// max_price为愿意为count个数字支付的最高总价，代币带转账手续费时包含手续费，None表示不限制
+    pub fn generate_numbers<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GenerateNumbers<'info>>,
+        count: u8,
+        max_price: Option<u64>,
+    ) -> Result<()> {
+        instructions::generate_numbers::handler(ctx, count, max_price)
+    }
+
+    /// 购买指定数字
+    /// 用户按数字池的指定数字价格购买范围内一个尚未发放的数字
+    /// max_price为愿意支付的最高价格，代币带转账手续费时包含手续费，None表示不限制
+    pub fn claim_specific_number(
+        ctx: Context<ClaimSpecificNumber>,
+        number: u32,
+        max_price: Option<u64>,
+    ) -> Result<()> {
+        instructions::claim_specific_number::handler(ctx, number, max_price)
+    }
+
+    /// 登记数字
+    /// 随机生成时未提供被选中数字的记录账户的，由数字所有者之后调用本指令创建NumberRecord，代币化之前需要登记
+    pub fn register_number(ctx: Context<RegisterNumber>, number: u32) -> Result<()> {
+        instructions::register_number::handler(ctx, number)
+    }
+
+    /// 转移数字
+    /// 数字所有者把一个数字转给另一个钱包，接收方在该数字池中没有用户账户时由发送方创建
+    pub fn transfer_number(
+        ctx: Context<TransferNumber>,
+        number: u32,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        instructions::transfer_number::handler(ctx, number, recipient)
+    }
+
+    /// 代币化数字
+    /// 数字所有者为数字铸造一个0位小数、供应量为1的SPL代币，之后代币持有者即为数字所有者
+    pub fn tokenize_number(ctx: Context<TokenizeNumber>, number: u32) -> Result<()> {
+        instructions::tokenize_number::handler(ctx, number)
+    }
+
+    /// 提交生成请求（提交-揭示模式第一步）
+    /// 用户提交秘密值的哈希承诺，生成费用暂存在待揭示PDA中
+    /// max_price为愿意支付的最高价格，None表示不限制
+    pub fn commit_generation(
+        ctx: Context<CommitGeneration>,
+        commitment: [u8; 32],
+        max_price: Option<u64>,
+    ) -> Result<()> {
+        instructions::commit_generation::handler(ctx, commitment, max_price)
+    }
+
+    /// 揭示秘密值并生成数字（提交-揭示模式第二步）
+    /// 必须在提交后至少若干个slot才能调用，随机数由秘密值和提交之后的区块哈希共同决定
+    pub fn reveal_generation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RevealGeneration<'info>>,
+        secret: [u8; 32],
+    ) -> Result<()> {
+        instructions::reveal_generation::handler(ctx, secret)
+    }
+
+    /// 退还生成请求
+    /// 暂停或数字已全部发放导致无法揭示时全额退款
+    /// 用户在有效期内可以揭示却没有揭示的，过期后扣下一部分费用转入财库，其余费用和租金退还给用户
+    pub fn refund_generation(ctx: Context<RefundGeneration>) -> Result<()> {
+        instructions::refund_generation::handler(ctx)
+    }
+
+    /// 更新数字池
+    /// 只有数字池管理员可以调用，修改生成价格、奖级表和指定数字价格倍数
+    /// 奖级表在开启轮次时保存，修改只影响之后开启的轮次
+    pub fn update_pool(
+        ctx: Context<UpdatePool>,
+        generation_price: Option<u64>,
+        prize_tiers: Option<Vec<PrizeTier>>,
+        specific_number_premium_bps: Option<u32>,
+    ) -> Result<()> {
+        instructions::update_pool::handler(
+            ctx,
+            generation_price,
+            prize_tiers,
+            specific_number_premium_bps,
+        )
+    }
+
+    /// 设置支付代币
+    /// 只有数字池管理员可以调用，之后生成数字以该代币按token_price（代币的最小单位）支付
+    pub fn set_payment_mint(ctx: Context<SetPaymentMint>, token_price: u64) -> Result<()> {
+        instructions::set_payment_mint::handler(ctx, token_price)
+    }
+
+    /// 清除支付代币
+    /// 只有数字池管理员可以调用，之后生成数字恢复以lamports支付
+    pub fn clear_payment_mint(ctx: Context<ClearPaymentMint>) -> Result<()> {
+        instructions::clear_payment_mint::handler(ctx)
+    }
+
+    /// 设置价格曲线
+    /// 只有数字池管理员可以调用，价格从生成价格开始随已生成的数字个数线性或指数上涨
+    pub fn set_price_curve(
+        ctx: Context<SetPriceCurve>,
+        price_curve: PriceCurve,
+        price_curve_slope: u64,
+    ) -> Result<()> {
+        instructions::set_price_curve::handler(ctx, price_curve, price_curve_slope)
+    }
+
+    /// 查询价格
+    /// 返回接下来生成count个数字的总价，客户端可以模拟调用读取返回值
+    pub fn quote_price(ctx: Context<QuotePrice>, count: u8) -> Result<u64> {
+        instructions::quote_price::handler(ctx, count)
+    }
+
+    /// 注册推荐人
+    /// 推荐人创建自己在数字池中的推荐奖励账户，之后用户生成数字时可以指定该账户
+    pub fn init_referral(ctx: Context<InitReferral>) -> Result<()> {
+        instructions::init_referral::handler(ctx)
+    }
+
+    /// 设置推荐比例
+    /// 只有数字池管理员可以调用，以lamports支付的生成费用按该比例分给推荐人
+    pub fn set_referral_bps(ctx: Context<SetReferralBps>, referral_bps: u16) -> Result<()> {
+        instructions::set_referral_bps::handler(ctx, referral_bps)
+    }
+
+    /// 领取推荐奖励
+    /// 推荐人领取推荐奖励账户中累计的全部奖励
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<()> {
+        instructions::claim_referral_rewards::handler(ctx)
+    }
+
+    /// 提议转移管理员（两步转移第一步）
+    /// 只有当前管理员可以调用，记录待接受的新管理员
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        instructions::propose_authority::handler(ctx, new_authority)
+    }
+
+    /// 接受管理员转移（两步转移第二步）
+    /// 必须由待接受的新管理员签名，避免地址填错导致管理权限丢失
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        instructions::accept_authority::handler(ctx)
+    }
+
+    /// 取消管理员转移
+    /// 只有当前管理员可以调用，清除待接受的新管理员
+    pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
+        instructions::cancel_authority_transfer::handler(ctx)
+    }
+
+    /// 迁移配置账户
+    /// 将已部署的旧版本配置账户扩容到当前结构大小，新增字段取默认值
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        instructions::migrate_config::handler(ctx)
+    }
+
+    /// 暂停生成
+    /// 管理员或守护者可以调用，暂停期间拒绝所有生成数字的操作
+    pub fn pause(ctx: Context<Pause>) -> Result<()> {
+        instructions::pause::handler(ctx)
+    }
+
+    /// 恢复生成
+    /// 只有程序管理员可以调用
+    pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
+        instructions::unpause::handler(ctx)
+    }
+
+    /// 设置守护者
+    /// 只有程序管理员可以调用，守护者只能暂停生成，设为默认地址即移除守护者
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        instructions::set_guardian::handler(ctx, guardian)
+    }
+
+    /// 设置随机数预言机
+    /// 只有程序管理员可以调用，将预言机程序设为默认地址即关闭预言机模式
+    pub fn set_oracle(
+        ctx: Context<SetOracle>,
+        oracle_program: Pubkey,
+        oracle_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::set_oracle::handler(ctx, oracle_program, oracle_authority)
+    }
+
+    /// 向预言机请求随机数（预言机模式第一步）
+    /// 生成费用暂存在请求PDA中，通过CPI向配置的预言机程序发起请求
+    /// max_price为愿意支付的最高价格，None表示不限制
+    pub fn request_vrf_generation(ctx: Context<RequestVrfGeneration>, max_price: Option<u64>) -> Result<()> {
+        instructions::request_vrf_generation::handler(ctx, max_price)
+    }
+
+    /// 预言机回填随机数（预言机模式回调）
+    /// 只能由配置中的预言机授权地址签名调用
+    pub fn fulfill_vrf_generation(
+        ctx: Context<FulfillVrfGeneration>,
+        randomness: [u8; 32],
+    ) -> Result<()> {
+        instructions::fulfill_vrf_generation::handler(ctx, randomness)
+    }
+
+    /// 使用回填的随机数领取数字（预言机模式第二步）
+    pub fn complete_vrf_generation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CompleteVrfGeneration<'info>>,
+    ) -> Result<()> {
+        instructions::complete_vrf_generation::handler(ctx)
+    }
+
+    /// 取消预言机请求
+    /// 预言机超时未回填，或请求已无法完成（生成暂停、数字发完、轮次截止）时，将暂存的费用和租金退还给用户
+    pub fn cancel_vrf_generation(ctx: Context<CancelVrfGeneration>) -> Result<()> {
+        instructions::cancel_vrf_generation::handler(ctx)
+    }
+
+    /// 开启轮次
+    /// 只有数字池管理员可以调用，设置本轮的截止时间，开奖之前收取的生成费用计入本轮奖池
+    pub fn open_round(ctx: Context<OpenRound>, close_time: i64) -> Result<()> {
+        instructions::open_round::handler(ctx, close_time)
+    }
+
+    /// 请求开奖
+    /// 截止时间之后任何人都可以调用，记录开奖使用的未来slot，每轮只能请求一次
+    pub fn request_draw(ctx: Context<RequestDraw>) -> Result<()> {
+        instructions::request_draw::handler(ctx)
+    }
+
+    /// 开奖
+    /// 请求开奖的目标slot之后任何人都可以调用，使用该slot的区块哈希挑选中奖数字
+    pub fn draw(ctx: Context<Draw>) -> Result<()> {
+        instructions::draw::handler(ctx)
+    }
+
+    /// 领取奖金
+    /// 命中奖级的数字的所有者领取该数字在本轮的奖金，未代币化的数字通过用户账户证明所有权，不需要数字记录
+    pub fn claim_prize(ctx: Context<ClaimPrize>, number: u32) -> Result<()> {
+        instructions::claim_prize::handler(ctx, number)
     }
 
     /// 提取资金
-    /// 只有程序管理员可以调用此函数
-    /// 将财库中的所有资金转移到指定的接收地址
-    pub fn withdraw_funds(ctx: Context<WithdrawFunds>) -> Result<()> {
-        instructions::withdraw_funds::handler(ctx)
+    /// 只有数字池管理员可以调用此函数
+    /// 从数字池的财库PDA提取指定金额到接收地址，财库需保留免租金最低余额和待领取的奖金，每次提取写入提取日志
+    /// 设置了分账表时不能提取，财库资金改由distribute分配
+    pub fn withdraw_funds(ctx: Context<WithdrawFunds>, amount: u64) -> Result<()> {
+        instructions::withdraw_funds::handler(ctx, amount)
+    }
+
+    /// 提取代币资金
+    /// 只有数字池管理员可以调用，从数字池的财库代币账户提取指定数量的代币到接收方代币账户
+    /// 设置了分账表时不能提取
+    pub fn withdraw_token_funds(ctx: Context<WithdrawTokenFunds>, amount: u64) -> Result<()> {
+        instructions::withdraw_token_funds::handler(ctx, amount)
+    }
+
+    /// 设置固定接收方
+    /// 只有程序管理员可以调用，设置或轮换数字池提取资金的接收方，传入默认值取消限制
+    /// 设置后数字池管理员只能向该地址提取lamports和代币
+    pub fn set_withdrawal_recipient(ctx: Context<SetWithdrawalRecipient>, recipient: Pubkey) -> Result<()> {
+        instructions::set_withdrawal_recipient::handler(ctx, recipient)
+    }
+
+    /// 设置分账表
+    /// 只有管理员可以调用，设置后各数字池财库中可分配的lamports和代币按份额分给各接收方，数字池管理员不能再提取lamports和代币
+    /// 还有已分得但尚未支付的lamports或代币时不能修改或取消
+    pub fn set_fee_split(ctx: Context<SetFeeSplit>, fee_recipients: Vec<FeeRecipient>) -> Result<()> {
+        instructions::set_fee_split::handler(ctx, fee_recipients)
+    }
+
+    /// 分账
+    /// 任何人都可以调用，把数字池财库中可分配的资金按份额记给各接收方，并向remaining_accounts中的接收方支付
+    pub fn distribute(ctx: Context<Distribute>) -> Result<()> {
+        instructions::distribute::handler(ctx)
+    }
+
+    /// 代币分账
+    /// 任何人都可以调用，把数字池财库代币账户中尚未分配的代币按份额记给各接收方，并向remaining_accounts中的接收方代币账户支付
+    pub fn distribute_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeTokens<'info>>,
+    ) -> Result<()> {
+        instructions::distribute_tokens::handler(ctx)
     }
 }
\ No newline at end of file
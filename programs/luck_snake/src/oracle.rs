@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+/// 外部预言机程序需要实现的请求指令名
+/// 账户顺序：请求方（签名）、预言机请求账户（可写）、付款人（签名、可写）、系统程序
+/// 指令数据：8字节Anchor判别器 + 32字节请求种子
+pub const REQUEST_RANDOMNESS_IX: &str = "request_randomness";
+
+/// 预言机回填随机数时调用的本程序指令名
+/// 第一个账户为签名的预言机授权地址，随后为指令所需的其余账户
+pub const FULFILL_CALLBACK_IX: &str = "fulfill_vrf_generation";
+
+/// 计算Anchor指令判别器：sha256("global:<指令名>")的前8字节
+pub fn instruction_discriminator(name: &str) -> [u8; 8] {
+    let preimage = format!("global:{}", name);
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash::hash(preimage.as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// 通过CPI向预言机程序请求随机数
+/// 请求方为本程序的VrfRequest PDA，使用signer_seeds代为签名
+pub fn request_randomness<'info>(
+    oracle_program: &AccountInfo<'info>,
+    requester: &AccountInfo<'info>,
+    oracle_request: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seed: [u8; 32],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut data = instruction_discriminator(REQUEST_RANDOMNESS_IX).to_vec();
+    data.extend_from_slice(&seed);
+    
+    let instruction = Instruction {
+        program_id: oracle_program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(requester.key(), true),
+            AccountMeta::new(oracle_request.key(), false),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(system_program.key(), false),
+        ],
+        data,
+    };
+    
+    invoke_signed(
+        &instruction,
+        &[
+            requester.clone(),
+            oracle_request.clone(),
+            payer.clone(),
+            system_program.clone(),
+            oracle_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+    
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use crate::errors::LuckSnakeError;
+
+/// SlotHashes sysvar中每条记录的大小：8字节slot + 32字节区块哈希
+const SLOT_HASH_ENTRY_LEN: usize = 8 + 32;
+
+/// 对多段种子做Keccak哈希，取前4字节（小端）作为32位随机值
+pub fn random_u32(seeds: &[&[u8]]) -> u32 {
+    let hash = keccak::hashv(seeds).to_bytes();
+    u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
+
+/// 读取SlotHashes sysvar中最新一条区块哈希
+pub fn latest_slot_hash(slot_hashes: &AccountInfo) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    require!(data.len() >= 8 + SLOT_HASH_ENTRY_LEN, LuckSnakeError::SlotHashNotFound);
+    
+    // 前8字节为记录条数，记录按slot降序排列，第一条即最新
+    let count = u64::from_le_bytes(data[..8].try_into().unwrap());
+    require!(count > 0, LuckSnakeError::SlotHashNotFound);
+    
+    Ok(data[16..16 + 32].try_into().unwrap())
+}
+
+/// 从SlotHashes sysvar中查找slot不早于target_slot的最早一条区块哈希
+/// sysvar数据按slot降序排列，直接读取原始字节二分查找，避免反序列化整个sysvar
+/// 目标slot尚未产生或已经滚出sysvar保存的窗口时返回SlotHashNotFound
+pub fn slot_hash_at_or_after(slot_hashes: &AccountInfo, target_slot: u64) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    require!(data.len() >= 8, LuckSnakeError::SlotHashNotFound);
+    
+    // 前8字节为记录条数
+    let count = u64::from_le_bytes(data[..8].try_into().unwrap()) as usize;
+    require!(
+        data.len() >= 8 + count * SLOT_HASH_ENTRY_LEN,
+        LuckSnakeError::SlotHashNotFound
+    );
+    let slot_at = |index: usize| {
+        let offset = 8 + index * SLOT_HASH_ENTRY_LEN;
+        u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+    };
+    
+    // 二分查找slot >= target_slot的记录数量（降序排列，这些记录构成前缀）
+    let (mut low, mut high) = (0, count);
+    while low < high {
+        let mid = (low + high) / 2;
+        if slot_at(mid) >= target_slot {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    
+    // low为0说明目标slot还没有区块哈希
+    // 所有记录都不早于目标slot且最旧一条不是目标slot，说明目标可能已滚出窗口
+    require!(low > 0, LuckSnakeError::SlotHashNotFound);
+    let index = low - 1;
+    require!(
+        low < count || slot_at(index) == target_slot,
+        LuckSnakeError::SlotHashNotFound
+    );
+    
+    let offset = 8 + index * SLOT_HASH_ENTRY_LEN + 8;
+    Ok(data[offset..offset + 32].try_into().unwrap())
+}
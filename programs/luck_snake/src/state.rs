@@ -1,63 +1,649 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
 
 /// 程序配置账户
-/// 存储程序的全局配置信息
+/// 存储程序的全局配置信息，各数字池的参数保存在各自的Pool账户中
+/// 新字段只能追加在末尾，且全零字节必须是有效的默认值，
+/// 这样已部署的旧配置账户可以通过migrate_config扩容后直接使用
 #[account]
 pub struct LuckSnakeConfig {
-    /// 程序管理员地址，拥有提取资金等管理权限
+    /// 程序管理员地址，负责创建数字池和管理全局设置
     pub authority: Pubkey,
-    /// 生成一个数字的价格（单位：lamports）
+    /// 已废弃：单池版本的生成价格，保留以维持账户布局，价格改由各数字池设置
     pub generation_price: u64,
-    /// 已生成的数字总数，用于统计和限制
+    /// 已废弃：单池版本的已生成数字总数，保留以维持账户布局
     pub total_generated_numbers: u32,
-    /// 财库地址，收集用户支付的费用
+    /// 已废弃：单池版本的财库地址，保留以维持账户布局，财库改由各数字池持有
     pub treasury: Pubkey,
     /// PDA bump种子，用于生成程序派生地址
     pub bump: u8,
+    /// 随机数预言机程序地址，默认值表示未启用预言机模式
+    pub oracle_program: Pubkey,
+    /// 预言机授权地址，只有它可以回填随机数
+    pub oracle_authority: Pubkey,
+    /// 待接受的新管理员地址，默认值表示没有进行中的管理员转移
+    pub pending_authority: Pubkey,
+    /// 是否暂停生成，暂停期间所有数字池都拒绝生成数字
+    pub paused: bool,
+    /// 守护者地址，可以暂停但不能恢复生成，默认值表示未设置
+    pub guardian: Pubkey,
+    /// 分账表，各数字池财库中可分配的lamports按份额分给这些接收方，未使用的条目为默认值
+    pub fee_recipients: [FeeRecipient; LuckSnakeConfig::MAX_FEE_RECIPIENTS],
+    /// 各分账接收方已分得但尚未支付的lamports，与分账表按下标对应，资金保存在配置账户中
+    pub fee_accrued: [u64; LuckSnakeConfig::MAX_FEE_RECIPIENTS],
+    /// 最近一次暂停的slot，0表示从未暂停
+    pub paused_slot: u64,
+    /// 最近一次恢复的slot，0表示从未恢复
+    pub unpaused_slot: u64,
+    /// 仍有待支付代币的代币分账账户数量，为0时所有数字池都没有待支付的代币分账
+    pub token_fee_accrual_count: u32,
 }
 
 impl LuckSnakeConfig {
     /// 账户大小：8字节判别器 + 32字节管理员 + 8字节价格 + 4字节计数 + 32字节财库 + 1字节bump
-    pub const LEN: usize = 8 + 32 + 8 + 4 + 32 + 1;
+    /// + 32字节预言机程序 + 32字节预言机授权 + 32字节待接受管理员 + 1字节暂停标志 + 32字节守护者
+    /// + 分账表 + 每个分账接收方8字节待支付金额 + 8字节暂停slot + 8字节恢复slot + 4字节代币分账账户数量
+    pub const LEN: usize = 8 + 32 + 8 + 4 + 32 + 1 + 32 + 32 + 32 + 1 + 32
+        + (FeeRecipient::LEN + 8) * LuckSnakeConfig::MAX_FEE_RECIPIENTS + 8 + 8 + 4;
     /// PDA种子前缀
     pub const SEED: &'static [u8] = b"config";
-    /// 生成价格：100,000 lamports (约0.0001 SOL)
-    pub const GENERATION_PRICE: u64 = 100_000;
-    /// 最大可生成数字数量：1000个（0-999）
+    /// 分账接收方数量上限
+    pub const MAX_FEE_RECIPIENTS: usize = 5;
+    
+    /// 是否设置了分账表
+    /// 设置后财库中的lamports只能通过distribute按份额分配，数字池管理员不能再提取lamports或代币
+    pub fn has_fee_split(&self) -> bool {
+        self.fee_recipients.iter().any(|entry| entry.share_bps > 0)
+    }
+    
+    /// 是否还有已分得但尚未支付的lamports或代币
+    /// 有待支付金额时不能修改分账表，避免接收方失去已分得的份额
+    pub fn has_undistributed_fees(&self) -> bool {
+        self.fee_accrued.iter().any(|accrued| *accrued > 0) || self.token_fee_accrual_count > 0
+    }
+    
+    /// 检查[start_slot, end_slot]期间是否有处于暂停状态的slot
+    /// 暂停区间为[paused_slot, unpaused_slot)，只记录最近一次暂停，更早的暂停区间不再可查
+    pub fn was_paused_between(&self, start_slot: u64, end_slot: u64) -> bool {
+        self.paused_slot != 0
+            && self.paused_slot <= end_slot
+            && (self.paused || self.unpaused_slot > start_slot)
+    }
+}
+
+/// 分账接收方
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeRecipient {
+    /// 接收方钱包地址
+    pub recipient: Pubkey,
+    /// 分得的份额（单位：基点），所有接收方的份额之和为10,000，0表示未使用
+    pub share_bps: u16,
+}
+
+impl FeeRecipient {
+    /// 序列化大小：32字节接收方 + 2字节份额
+    pub const LEN: usize = 32 + 2;
+}
+
+/// 代币分账账户
+/// 每个数字池的每种支付代币一个，记录各分账接收方已分得但尚未支付的代币
+/// 代币仍保存在财库代币账户中，可分配的数量需扣除这里的待支付总额
+#[account]
+pub struct TokenFeeAccrual {
+    /// 所属数字池地址
+    pub pool: Pubkey,
+    /// 代币的mint地址
+    pub mint: Pubkey,
+    /// 各分账接收方尚未支付的代币数量，与分账表按下标对应
+    pub accrued: [u64; LuckSnakeConfig::MAX_FEE_RECIPIENTS],
+    /// PDA bump种子
+    pub bump: u8,
+}
+
+impl TokenFeeAccrual {
+    /// 账户大小：8字节判别器 + 32字节数字池 + 32字节mint + 每个分账接收方8字节待支付数量 + 1字节bump
+    pub const LEN: usize = 8 + 32 + 32 + 8 * LuckSnakeConfig::MAX_FEE_RECIPIENTS + 1;
+    /// PDA种子前缀，与数字池地址和mint地址一起派生
+    pub const SEED: &'static [u8] = b"token_fee_accrual";
+    
+    /// 尚未支付的代币总数
+    pub fn total_accrued(&self) -> u64 {
+        self.accrued.iter().sum()
+    }
+}
+
+/// 数字池账户
+/// 每个数字池是一期独立的活动，拥有自己的管理员、价格、数字范围、财库和发放计数
+#[account]
+pub struct Pool {
+    /// 数字池编号，用于派生PDA地址
+    pub pool_id: u64,
+    /// 数字池管理员地址，可以修改价格和提取该池的资金
+    pub authority: Pubkey,
+    /// 生成一个数字的价格（单位：lamports）
+    pub generation_price: u64,
+    /// 本池已生成的数字总数
+    pub total_generated_numbers: u32,
+    /// 可生成数字的最小值（包含）
+    pub range_min: u32,
+    /// 可生成数字的最大值（包含）
+    pub range_max: u32,
+    /// 财库PDA地址，收集本池用户支付的费用
+    pub treasury: Pubkey,
+    /// PDA bump种子
+    pub bump: u8,
+    /// 已开启的轮次数量，同时也是下一轮的编号
+    pub round_count: u64,
+    /// 进行中（尚未开奖）轮次的截止时间，0表示没有进行中的轮次
+    pub round_close_time: i64,
+    /// 进行中的轮次收取的生成费用加上滚入的奖级份额，开奖时计入该轮奖池
+    pub round_pot: u64,
+    /// 奖级表，按末尾数字的匹配位数划分，未使用的奖级匹配位数为0
+    pub prize_tiers: [PrizeTier; Pool::MAX_PRIZE_TIERS],
+    /// 已开奖但尚未领取的奖金总额，提取资金时不能动用
+    pub reserved_prizes: u64,
+    /// 指定数字购买的价格倍数（单位：基点，10,000为生成价格的1倍）
+    pub specific_number_premium_bps: u32,
+    /// 支付代币的mint地址，默认值表示以lamports支付
+    pub payment_mint: Pubkey,
+    /// 以代币支付时生成一个数字的价格（单位：代币的最小单位）
+    pub token_price: u64,
+    /// 价格曲线，决定价格如何随已生成的数字个数上涨
+    pub price_curve: PriceCurve,
+    /// 价格曲线的斜率：线性曲线为每个数字的涨价金额，指数曲线为每个数字的涨幅（单位：基点）
+    pub price_curve_slope: u64,
+    /// 有推荐人时分给推荐人的费用比例（单位：基点）
+    pub referral_bps: u16,
+    /// 转入财库的lamports生成费用累计
+    pub treasury_revenue: u64,
+    /// 分给推荐人的lamports生成费用累计，与财库收入之和等于总收入
+    pub referral_revenue: u64,
+    /// 提取资金的固定接收方，由程序管理员设置，默认值表示由数字池管理员在每次提取时指定
+    pub withdrawal_recipient: Pubkey,
+}
+
+impl Pool {
+    /// 账户大小：8字节判别器 + 8字节编号 + 32字节管理员 + 8字节价格 + 4字节计数
+    /// + 4字节最小值 + 4字节最大值 + 32字节财库 + 1字节bump + 8字节轮次数量
+    /// + 8字节截止时间 + 8字节奖池 + 奖级表 + 8字节待领取奖金 + 4字节指定数字价格倍数
+    /// + 32字节支付代币 + 8字节代币价格 + 1字节价格曲线 + 8字节曲线斜率
+    /// + 2字节推荐比例 + 8字节财库收入 + 8字节推荐收入 + 32字节固定接收方
+    pub const LEN: usize = 8 + 8 + 32 + 8 + 4 + 4 + 4 + 32 + 1 + 8 + 8 + 8
+        + PrizeTier::LEN * Pool::MAX_PRIZE_TIERS + 8 + 4 + 32 + 8 + 1 + 8 + 2 + 8 + 8 + 32;
+    /// PDA种子前缀，与数字池编号的小端字节一起派生地址
+    pub const SEED: &'static [u8] = b"pool";
+    /// 财库代币账户的PDA种子前缀，与数字池地址和支付代币的mint地址一起派生地址
+    pub const TREASURY_TOKEN_SEED: &'static [u8] = b"treasury_token";
+    /// 可设置的最低生成价格：1,000 lamports
+    pub const MIN_GENERATION_PRICE: u64 = 1_000;
+    /// 可设置的最高生成价格：10 SOL
+    pub const MAX_GENERATION_PRICE: u64 = 10_000_000_000;
+    /// 数字范围的最大容量：1000个，决定数字位图的大小
     pub const MAX_NUMBERS: u32 = 1000;
+    /// 批量生成一次最多发放的数字个数，受交易账户数量和计算单元预算限制
+    pub const MAX_BATCH_COUNT: u8 = 10;
+    /// 奖级数量上限
+    pub const MAX_PRIZE_TIERS: usize = 3;
+    /// 匹配位数上限：数字范围最多1000个数字，末尾3位相同即为完全匹配
+    pub const MAX_MATCH_DIGITS: u8 = 3;
+    /// 创建数字池时默认的奖级：完全匹配获得奖池的50%
+    pub const DEFAULT_PRIZE_TIERS: [PrizeTier; Pool::MAX_PRIZE_TIERS] = [
+        PrizeTier { match_digits: 3, share_bps: 5_000 },
+        PrizeTier { match_digits: 0, share_bps: 0 },
+        PrizeTier { match_digits: 0, share_bps: 0 },
+    ];
+    /// 基点的分母
+    pub const BPS_DENOMINATOR: u64 = 10_000;
+    /// 指定数字价格倍数的上限：生成价格的10倍
+    pub const MAX_SPECIFIC_NUMBER_PREMIUM_BPS: u32 = 100_000;
+    /// 推荐比例的上限：生成费用的50%
+    pub const MAX_REFERRAL_BPS: u16 = 5_000;
+    /// 指数曲线每个数字涨幅的上限：10%
+    pub const MAX_EXPONENTIAL_SLOPE_BPS: u64 = 1_000;
+    /// 指数曲线计算涨价倍数时使用的定点数精度
+    const PRICE_FACTOR_SCALE: u128 = 1_000_000_000_000;
+    
+    /// 数字范围内的数字个数
+    pub fn number_count(&self) -> u32 {
+        self.range_max - self.range_min + 1
+    }
+    
+    /// 是否以代币支付生成费用
+    pub fn pays_with_token(&self) -> bool {
+        self.payment_mint != Pubkey::default()
+    }
+    
+    /// 价格曲线的起始价格，以代币支付时为代币价格，否则为lamports价格
+    pub fn base_price(&self) -> u64 {
+        if self.pays_with_token() {
+            self.token_price
+        } else {
+            self.generation_price
+        }
+    }
+    
+    /// 已生成issued个数字时，下一个数字的价格
+    /// 线性曲线：起始价格 + 斜率 × issued；指数曲线：起始价格 × (1 + 斜率/10,000)^issued
+    /// 价格超出u64范围时返回None
+    pub fn price_at(&self, issued: u32) -> Option<u64> {
+        let base_price = self.base_price();
+        match self.price_curve {
+            PriceCurve::Flat => Some(base_price),
+            PriceCurve::Linear => self
+                .price_curve_slope
+                .checked_mul(issued as u64)?
+                .checked_add(base_price),
+            PriceCurve::Exponential => {
+                // 以定点数按平方求幂计算(1 + 斜率/10,000)^issued
+                let mut factor = Self::PRICE_FACTOR_SCALE;
+                let mut step = Self::PRICE_FACTOR_SCALE
+                    * (Self::BPS_DENOMINATOR + self.price_curve_slope) as u128
+                    / Self::BPS_DENOMINATOR as u128;
+                let mut exponent = issued;
+                while exponent > 0 {
+                    if exponent & 1 == 1 {
+                        factor = factor.checked_mul(step)? / Self::PRICE_FACTOR_SCALE;
+                    }
+                    exponent >>= 1;
+                    if exponent > 0 {
+                        step = step.checked_mul(step)? / Self::PRICE_FACTOR_SCALE;
+                    }
+                }
+                u64::try_from((base_price as u128).checked_mul(factor)? / Self::PRICE_FACTOR_SCALE).ok()
+            }
+        }
+    }
+    
+    /// 下一个数字的价格
+    pub fn current_price(&self) -> Option<u64> {
+        self.price_at(self.total_generated_numbers)
+    }
+    
+    /// 接下来连续生成count个数字的总价
+    pub fn batch_price(&self, count: u8) -> Option<u64> {
+        (0..count as u32).try_fold(0u64, |total, offset| {
+            total.checked_add(self.price_at(self.total_generated_numbers + offset)?)
+        })
+    }
+    
+    /// 购买指定数字的价格：下一个数字的价格乘以价格倍数
+    pub fn specific_number_price(&self) -> Option<u64> {
+        let price = self.current_price()? as u128 * self.specific_number_premium_bps as u128
+            / Self::BPS_DENOMINATOR as u128;
+        u64::try_from(price).ok()
+    }
+    
+    /// 有推荐人时从生成费用中分给推荐人的金额
+    pub fn referral_share(&self, amount: u64) -> u64 {
+        (amount as u128 * self.referral_bps as u128 / Self::BPS_DENOMINATOR as u128) as u64
+    }
+    
+    /// 记录以lamports收取的生成费用
+    /// 有进行中的轮次时，转入财库的部分计入该轮奖池，推荐奖励不计入
+    /// 没有进行中的轮次时费用不计入奖池，数字池管理员可以直接提取
+    /// 奖金以lamports发放，以代币支付的费用不经过这里，也不计入奖池
+    pub fn record_revenue(&mut self, treasury_amount: u64, referral_amount: u64) {
+        self.treasury_revenue += treasury_amount;
+        self.referral_revenue += referral_amount;
+        if self.has_open_round() {
+            self.round_pot += treasury_amount;
+        }
+    }
+    
+    /// 检查recipient是否可以接收提取的资金
+    /// 没有设置固定接收方时任何地址都可以
+    pub fn accepts_withdrawal_to(&self, recipient: &Pubkey) -> bool {
+        self.withdrawal_recipient == Pubkey::default() || self.withdrawal_recipient == *recipient
+    }
+    
+    /// 是否有尚未开奖的轮次
+    pub fn has_open_round(&self) -> bool {
+        self.round_close_time != 0
+    }
+    
+    /// 财库中不能提取或分配的lamports
+    /// 包括已开奖未领取的奖金和奖池（进行中轮次的费用和滚入下一轮的奖级份额）
+    pub fn locked_funds(&self) -> u64 {
+        self.reserved_prizes.saturating_add(self.round_pot)
+    }
+    
+    /// 当前是否可以发放数字
+    /// 进行中的轮次截止之后、开奖之前不再发放数字，避免在开奖前临时买入
+    pub fn is_accepting_numbers(&self, now: i64) -> bool {
+        !self.has_open_round() || now < self.round_close_time
+    }
+}
+
+/// 价格曲线
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PriceCurve {
+    /// 固定价格
+    #[default]
+    Flat,
+    /// 每生成一个数字，价格增加固定金额
+    Linear,
+    /// 每生成一个数字，价格按固定比例上涨
+    Exponential,
+}
+
+/// 奖级
+/// 数字与中奖数字的末尾match_digits位十进制数字相同即命中该奖级
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrizeTier {
+    /// 需要匹配的末尾位数，0表示未使用
+    pub match_digits: u8,
+    /// 该奖级分得的奖池份额（单位：基点），由命中该奖级的数字平分
+    pub share_bps: u16,
+}
+
+impl PrizeTier {
+    /// 序列化大小：1字节匹配位数 + 2字节份额
+    pub const LEN: usize = 1 + 2;
+    
+    /// 检查数字是否命中该奖级
+    pub fn matches(&self, number: u32, winning_number: u32) -> bool {
+        let modulus = 10u32.pow(self.match_digits as u32);
+        self.match_digits > 0 && number % modulus == winning_number % modulus
+    }
+}
+
+/// 轮次账户
+/// 数字池管理员开启一轮后，截止时间之后任何人都可以请求开奖并在目标slot之后开奖，命中奖级的数字的所有者领取奖金
+#[account]
+pub struct Round {
+    /// 所属数字池地址
+    pub pool: Pubkey,
+    /// 轮次编号，在数字池内从0开始递增
+    pub round_id: u64,
+    /// 截止时间（Unix时间戳），之后才能开奖
+    pub close_time: i64,
+    /// 开奖使用的区块哈希所在的slot，请求开奖时确定，0表示尚未请求开奖
+    pub draw_slot: u64,
+    /// 开奖时计入本轮的奖池金额（单位：lamports）
+    pub pot: u64,
+    /// 中奖数字，开奖后有效
+    pub winning_number: u32,
+    /// 是否已开奖
+    pub drawn: bool,
+    /// PDA bump种子
+    pub bump: u8,
+    /// 开启轮次时的奖级表快照，之后修改数字池的奖级不影响本轮
+    pub prize_tiers: [PrizeTier; Pool::MAX_PRIZE_TIERS],
+    /// 各奖级命中的数字个数
+    pub tier_winners: [u32; Pool::MAX_PRIZE_TIERS],
+    /// 各奖级每个命中数字可领取的奖金（单位：lamports）
+    pub tier_prizes: [u64; Pool::MAX_PRIZE_TIERS],
+    /// 已领取奖金的数字位图，第n位为1表示数字range_min + n已领取
+    pub claimed: [u8; NumberPool::BITMAP_BYTES],
+    /// 开奖时已发放数字位图的快照，只有开奖前发放的数字可以领奖
+    pub eligible: [u8; NumberPool::BITMAP_BYTES],
+}
+
+impl Round {
+    /// 账户大小：8字节判别器 + 32字节数字池 + 8字节编号 + 8字节截止时间 + 8字节开奖slot + 8字节奖池
+    /// + 4字节中奖数字 + 1字节开奖标志 + 1字节bump + 奖级表 + 各奖级命中个数 + 各奖级奖金 + 领取位图 + 开奖时的发放位图
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 4 + 1 + 1
+        + PrizeTier::LEN * Pool::MAX_PRIZE_TIERS
+        + 4 * Pool::MAX_PRIZE_TIERS
+        + 8 * Pool::MAX_PRIZE_TIERS
+        + NumberPool::BITMAP_BYTES
+        + NumberPool::BITMAP_BYTES;
+    /// PDA种子前缀，与数字池地址和轮次编号的小端字节一起派生地址
+    pub const SEED: &'static [u8] = b"round";
+    /// 请求开奖后至少经过的slot数，开奖使用该slot之后的区块哈希
+    pub const DRAW_DELAY_SLOTS: u64 = 2;
+    /// 开奖请求的有效期（slot数），需小于SlotHashes保存的512个slot
+    /// 过期后目标slot的区块哈希可能已滚出窗口，开奖改用开奖时最新的区块哈希，不能重新请求
+    pub const DRAW_EXPIRY_SLOTS: u64 = 400;
+    
+    /// 是否已请求开奖
+    pub fn is_draw_requested(&self) -> bool {
+        self.draw_slot != 0
+    }
+    
+    /// 检查开奖请求是否已过期
+    pub fn is_draw_expired(&self, current_slot: u64) -> bool {
+        current_slot > self.draw_slot + Self::DRAW_EXPIRY_SLOTS
+    }
+    
+    /// 查找数字命中的最高奖级（匹配位数最多的奖级）
+    /// 一个数字只能按一个奖级领奖
+    pub fn winning_tier(&self, number: u32) -> Option<usize> {
+        self.prize_tiers
+            .iter()
+            .enumerate()
+            .filter(|(_, tier)| tier.matches(number, self.winning_number))
+            .max_by_key(|(_, tier)| tier.match_digits)
+            .map(|(index, _)| index)
+    }
+    
+    /// 检查位图中第index位对应的数字是否已领取奖金
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.claimed[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+    
+    /// 将位图中第index位对应的数字标记为已领取
+    pub fn mark_claimed(&mut self, index: u32) {
+        self.claimed[(index / 8) as usize] |= 1 << (index % 8);
+    }
+    
+    /// 检查位图中第index位对应的数字是否在开奖前已发放
+    pub fn is_eligible(&self, index: u32) -> bool {
+        self.eligible[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+}
+
+/// 财库账户
+/// 由本程序拥有的PDA，保管用户支付的费用
+/// 提取资金时程序直接修改lamports，无需财库私钥签名
+#[account]
+pub struct Treasury {
+    /// PDA bump种子
+    pub bump: u8,
+}
+
+impl Treasury {
+    /// 账户大小：8字节判别器 + 1字节bump
+    pub const LEN: usize = 8 + 1;
+    /// PDA种子前缀，与数字池地址一起派生财库地址
+    pub const SEED: &'static [u8] = b"treasury";
+}
+
+/// 提取记录
+/// 记录一次提取的时间、金额和接收地址
+#[zero_copy]
+pub struct WithdrawalRecord {
+    /// 提取时间（Unix时间戳）
+    pub timestamp: i64,
+    /// 提取金额（单位：lamports）
+    pub amount: u64,
+    /// 接收方地址
+    pub recipient: Pubkey,
+}
+
+/// 提取日志账户
+/// 环形缓冲区，保存最近CAPACITY次提取记录，写满后覆盖最早的记录
+#[account(zero_copy)]
+pub struct WithdrawalLog {
+    /// 累计提取次数，下一条记录写入位置为 total_withdrawals % CAPACITY
+    pub total_withdrawals: u64,
+    /// PDA bump种子
+    pub bump: u8,
+    /// 对齐填充
+    pub _padding: [u8; 7],
+    /// 提取记录
+    pub records: [WithdrawalRecord; WithdrawalLog::CAPACITY],
+}
+
+impl WithdrawalLog {
+    /// 保存的记录条数
+    pub const CAPACITY: usize = 64;
+    /// 账户大小：8字节判别器 + 8字节计数 + 1字节bump + 7字节填充 + 记录（每条48字节）
+    pub const LEN: usize = 8 + 8 + 1 + 7 + Self::CAPACITY * (8 + 8 + 32);
+    /// PDA种子前缀，与数字池地址一起派生日志地址
+    pub const SEED: &'static [u8] = b"withdrawal_log";
+    
+    /// 追加一条提取记录，写满后覆盖最早的记录
+    pub fn push(&mut self, record: WithdrawalRecord) {
+        let index = (self.total_withdrawals % Self::CAPACITY as u64) as usize;
+        self.records[index] = record;
+        self.total_withdrawals += 1;
+    }
+}
+
+/// 已发放数字位图账户
+/// 每一位对应一个数字，置1表示该数字已被发放
+/// 使用zero_copy避免每次生成都反序列化整个位图
+#[account(zero_copy)]
+pub struct NumberPool {
+    /// 池容量，即可发放的数字总数，创建时取自数字池的数字范围
+    pub capacity: u32,
+    /// PDA bump种子
+    pub bump: u8,
+    /// 对齐填充
+    pub _padding: [u8; 3],
+    /// 位图，第n位为1表示数字range_min + n已被发放
+    pub bitmap: [u8; NumberPool::BITMAP_BYTES],
+}
+
+impl NumberPool {
+    /// 位图字节数：按MAX_NUMBERS位向上取整到8字节
+    pub const BITMAP_BYTES: usize = (Pool::MAX_NUMBERS as usize).div_ceil(64) * 8;
+    /// 账户大小：8字节判别器 + 4字节容量 + 1字节bump + 3字节填充 + 位图
+    pub const LEN: usize = 8 + 4 + 1 + 3 + Self::BITMAP_BYTES;
+    /// PDA种子前缀，与数字池地址一起派生位图地址
+    pub const SEED: &'static [u8] = b"number_pool";
+    
+    /// 检查位图中第index位对应的数字是否已被发放
+    pub fn is_issued(&self, index: u32) -> bool {
+        self.bitmap[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+    
+    /// 将位图中第index位对应的数字标记为已发放
+    pub fn mark_issued(&mut self, index: u32) {
+        self.bitmap[(index / 8) as usize] |= 1 << (index % 8);
+    }
+    
+    /// 查找第k个（从0开始计数）尚未发放的数字在位图中的位置
+    /// 按字节统计空闲位数跳过整字节，只在目标字节内逐位查找
+    pub fn nth_free(&self, k: u32) -> Option<u32> {
+        let mut remaining = k;
+        for (index, byte) in self.bitmap.iter().enumerate() {
+            let base = index as u32 * 8;
+            if base >= self.capacity {
+                break;
+            }
+            
+            // 最后一个字节可能只有部分位在容量范围内
+            let valid_bits = (self.capacity - base).min(8);
+            let mask = (((1u16 << valid_bits) - 1) & 0xFF) as u8;
+            let free = (!byte & mask).count_ones();
+            if remaining >= free {
+                remaining -= free;
+                continue;
+            }
+            
+            for bit in 0..valid_bits {
+                if byte & (1 << bit) == 0 {
+                    if remaining == 0 {
+                        return Some(base + bit);
+                    }
+                    remaining -= 1;
+                }
+            }
+        }
+        None
+    }
 }
 
 /// 数字记录账户
 /// 记录每个生成的数字及其所有者信息
 #[account]
 pub struct NumberRecord {
-    /// 生成的数字（0-999）
+    /// 所属数字池地址
+    pub pool: Pubkey,
+    /// 生成的数字
     pub number: u32,
     /// 数字的所有者地址
     pub owner: Pubkey,
-    /// 生成时间戳
+    /// 生成时间戳，生成时未创建记录的数字为通过register_number登记的时间
     pub generated_at: i64,
     /// PDA bump种子
     pub bump: u8,
 }
 
 impl NumberRecord {
-    /// 账户大小：8字节判别器 + 4字节数字 + 32字节所有者 + 8字节时间戳 + 1字节bump
-    pub const LEN: usize = 8 + 4 + 32 + 8 + 1;
+    /// 账户大小：8字节判别器 + 32字节数字池 + 4字节数字 + 32字节所有者 + 8字节时间戳 + 1字节bump
+    pub const LEN: usize = 8 + 32 + 4 + 32 + 8 + 1;
+    /// PDA种子前缀，与数字池地址和数字的小端字节一起派生地址，保证每个池内数字唯一
+    pub const SEED: &'static [u8] = b"number";
     
-    /// 生成数字记录的PDA种子
-    /// 将"number"前缀和数字值组合成种子
-    pub fn seed(number: u32) -> Vec<u8> {
-        let mut seed = b"number".to_vec();
-        seed.extend_from_slice(&number.to_le_bytes());
-        seed
+    /// 检查holder是否持有该数字
+    /// 代币化之后owner记为数字的mint地址，持有该mint代币的钱包才是所有者
+    pub fn is_held_by(&self, holder: &Pubkey, token_account: Option<&TokenAccount>) -> bool {
+        self.owner == *holder
+            || token_account.is_some_and(|token_account| {
+                token_account.mint == self.owner
+                    && token_account.owner == *holder
+                    && token_account.amount == 1
+            })
     }
 }
 
+/// 数字代币信息账户
+/// 数字代币化之后，由mint地址派生，保存代币对应的数字和生成时间，便于钱包和索引器查询
+#[account]
+pub struct NumberToken {
+    /// 所属数字池地址
+    pub pool: Pubkey,
+    /// 代币的mint地址
+    pub mint: Pubkey,
+    /// 代币对应的数字
+    pub number: u32,
+    /// 数字的生成时间戳
+    pub generated_at: i64,
+    /// PDA bump种子
+    pub bump: u8,
+}
+
+impl NumberToken {
+    /// 账户大小：8字节判别器 + 32字节数字池 + 32字节mint + 4字节数字 + 8字节时间戳 + 1字节bump
+    pub const LEN: usize = 8 + 32 + 32 + 4 + 8 + 1;
+    /// PDA种子前缀，与mint地址一起派生地址
+    pub const SEED: &'static [u8] = b"number_token";
+    /// mint的PDA种子前缀，与数字池地址和数字的小端字节一起派生mint地址
+    pub const MINT_SEED: &'static [u8] = b"number_mint";
+}
+
+/// 推荐奖励账户
+/// 推荐人在每个数字池中注册一个，累积被推荐用户支付的费用中分给推荐人的部分
+#[account]
+pub struct ReferralRewards {
+    /// 所属数字池地址
+    pub pool: Pubkey,
+    /// 推荐人的钱包地址
+    pub referrer: Pubkey,
+    /// 尚未领取的奖励（单位：lamports），保存在本账户中
+    pub accrued: u64,
+    /// 累计获得的奖励（单位：lamports）
+    pub total_earned: u64,
+    /// PDA bump种子
+    pub bump: u8,
+}
+
+impl ReferralRewards {
+    /// 账户大小：8字节判别器 + 32字节数字池 + 32字节推荐人 + 8字节待领取奖励 + 8字节累计奖励 + 1字节bump
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 1;
+    /// PDA种子前缀，与数字池地址和推荐人地址一起派生
+    pub const SEED: &'static [u8] = b"referral";
+}
+
 /// 用户账户
 /// 存储用户拥有的所有数字和相关信息
 #[account]
 pub struct UserAccount {
+    /// 所属数字池地址
+    pub pool: Pubkey,
     /// 用户的钱包地址
     pub user: Pubkey,
     /// 用户拥有的所有数字列表
@@ -69,12 +655,12 @@ pub struct UserAccount {
 }
 
 impl UserAccount {
-    /// 基础账户大小：8字节判别器 + 32字节用户 + 4字节向量长度 + 8字节nonce + 1字节bump
+    /// 基础账户大小：8字节判别器 + 32字节数字池 + 32字节用户 + 4字节向量长度 + 8字节nonce + 1字节bump
     /// 初始不包含任何数字，每个数字需要额外4字节
-    pub const BASE_LEN: usize = 8 + 32 + 4 + 8 + 1;
+    pub const BASE_LEN: usize = 8 + 32 + 32 + 4 + 8 + 1;
     /// 每个数字占用的空间
     pub const NUMBER_SIZE: usize = 4;
-    /// PDA种子前缀
+    /// PDA种子前缀，与数字池地址和用户地址一起派生，每个用户在每个池中各有一个账户
     pub const SEED: &'static [u8] = b"user";
     
     /// 根据数字数量计算所需的账户大小
@@ -91,4 +677,103 @@ impl UserAccount {
     pub fn space_after_adding_number(&self) -> usize {
         Self::space_for_numbers(self.numbers.len() + 1)
     }
-}
\ No newline at end of file
+    
+    /// 计算移除一个数字后的账户大小
+    pub fn space_after_removing_number(&self) -> usize {
+        Self::space_for_numbers(self.numbers.len().saturating_sub(1))
+    }
+    
+    /// 计算添加count个数字后的账户大小
+    pub fn space_after_adding_numbers(&self, count: u8) -> usize {
+        Self::space_for_numbers(self.numbers.len() + count as usize)
+    }
+}
+
+/// 待揭示的生成请求账户
+/// 提交-揭示模式下保存用户的哈希承诺和预付的生成费用
+#[account]
+pub struct PendingGeneration {
+    /// 所属数字池地址
+    pub pool: Pubkey,
+    /// 提交请求的用户地址
+    pub user: Pubkey,
+    /// 秘密值的Keccak哈希承诺
+    pub commitment: [u8; 32],
+    /// 暂存在本账户中的生成费用（单位：lamports）
+    pub amount: u64,
+    /// 提交时的slot
+    pub commit_slot: u64,
+    /// PDA bump种子
+    pub bump: u8,
+}
+
+impl PendingGeneration {
+    /// 账户大小：8字节判别器 + 32字节数字池 + 32字节用户 + 32字节承诺 + 8字节金额 + 8字节slot + 1字节bump
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1;
+    /// PDA种子前缀，与数字池地址和用户地址一起派生
+    pub const SEED: &'static [u8] = b"pending";
+    /// 提交后至少经过的slot数，揭示时使用该slot之后的区块哈希
+    pub const REVEAL_DELAY_SLOTS: u64 = 2;
+    /// 承诺的有效期（slot数），需小于SlotHashes保存的512个slot
+    pub const EXPIRY_SLOTS: u64 = 400;
+    /// 提交时距离进行中轮次的截止时间至少保留的秒数
+    /// 大于有效期400个slot的正常时长，揭示窗口不会被轮次截止打断
+    pub const ROUND_CLOSE_BUFFER_SECONDS: i64 = 240;
+    /// 用户放弃揭示、过期退款时不退还的费用比例（单位：基点）
+    /// 揭示slot之后用户已能算出结果，全额退款会让用户放弃不满意的结果免费重新生成
+    pub const EXPIRY_FORFEIT_BPS: u64 = 5_000;
+    
+    /// 揭示时使用的区块哈希所在的slot
+    pub fn reveal_slot(&self) -> u64 {
+        self.commit_slot + Self::REVEAL_DELAY_SLOTS
+    }
+    
+    /// 最后一个可以揭示的slot
+    pub fn expiry_slot(&self) -> u64 {
+        self.commit_slot + Self::EXPIRY_SLOTS
+    }
+    
+    /// 检查承诺是否已过期
+    pub fn is_expired(&self, current_slot: u64) -> bool {
+        current_slot > self.expiry_slot()
+    }
+    
+    /// 过期退款时转入财库、不退还的费用
+    pub fn forfeited_amount(&self) -> u64 {
+        (self.amount as u128 * Self::EXPIRY_FORFEIT_BPS as u128 / Pool::BPS_DENOMINATOR as u128) as u64
+    }
+}
+
+/// 预言机随机数请求账户
+/// 预言机模式下保存预付的生成费用和预言机回填的随机数
+#[account]
+pub struct VrfRequest {
+    /// 所属数字池地址
+    pub pool: Pubkey,
+    /// 发起请求的用户地址
+    pub user: Pubkey,
+    /// 暂存在本账户中的生成费用（单位：lamports）
+    pub amount: u64,
+    /// 发起请求时的slot
+    pub request_slot: u64,
+    /// 预言机回填的随机数
+    pub randomness: [u8; 32],
+    /// 预言机是否已回填随机数
+    pub fulfilled: bool,
+    /// PDA bump种子
+    pub bump: u8,
+}
+
+impl VrfRequest {
+    /// 账户大小：8字节判别器 + 32字节数字池 + 32字节用户 + 8字节金额 + 8字节slot + 32字节随机数 + 1字节标记 + 1字节bump
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 32 + 1 + 1;
+    /// PDA种子前缀，与数字池地址和用户地址一起派生
+    pub const SEED: &'static [u8] = b"vrf_request";
+    /// 预言机回填的超时时间（slot数），超时未回填的请求可以取消退款
+    pub const TIMEOUT_SLOTS: u64 = 150;
+    
+    /// 检查请求是否已超时
+    pub fn is_timed_out(&self, current_slot: u64) -> bool {
+        current_slot > self.request_slot + Self::TIMEOUT_SLOTS
+    }
+}
@@ -0,0 +1,214 @@
+//! solana-program-test集成测试的公共工具
+//! 以原生处理函数加载luck_snake和mock_oracle，可用warp_to_slot直接跳到任意slot
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::{Instruction, InstructionError};
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::{system_program, AccountDeserialize, InstructionData, ToAccountMetas};
+use luck_snake::errors::LuckSnakeError;
+use luck_snake::state::*;
+use solana_keypair::Keypair;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use solana_transaction_error::TransactionError;
+
+/// 测试数字池编号
+pub const POOL_ID: u64 = 0;
+/// 测试数字池的生成价格（单位：lamports）
+pub const GENERATION_PRICE: u64 = 10_000;
+/// 测试数字池的数字范围
+pub const RANGE_MIN: u32 = 1;
+pub const RANGE_MAX: u32 = 100;
+
+/// Anchor的entry要求账户切片与AccountInfo同生命周期，原生处理函数按测试需要泄漏一份拷贝
+fn luck_snake_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    luck_snake::entry(program_id, accounts, data)
+}
+
+fn mock_oracle_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    mock_oracle::entry(program_id, accounts, data)
+}
+
+/// 启动加载了luck_snake和mock_oracle的测试环境
+pub async fn start() -> ProgramTestContext {
+    let mut program_test = ProgramTest::new("luck_snake", luck_snake::ID, processor!(luck_snake_entry));
+    program_test.add_program("mock_oracle", mock_oracle::ID, processor!(mock_oracle_entry));
+    program_test.prefer_bpf(false);
+    program_test.start_with_context().await
+}
+
+/// 读取tests/fixtures中的预言机授权私钥
+pub fn oracle_authority() -> Keypair {
+    let bytes: Vec<u8> = include_str!("../../../../tests/fixtures/mock_oracle_authority.json")
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|byte| byte.trim().parse().unwrap())
+        .collect();
+    let keypair = Keypair::try_from(bytes.as_slice()).unwrap();
+    assert_eq!(keypair.pubkey(), mock_oracle::ORACLE_AUTHORITY);
+    keypair
+}
+
+pub fn config_pda() -> Pubkey {
+    Pubkey::find_program_address(&[LuckSnakeConfig::SEED], &luck_snake::ID).0
+}
+
+pub fn pool_pda(pool_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[Pool::SEED, &pool_id.to_le_bytes()], &luck_snake::ID).0
+}
+
+pub fn number_pool_pda(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[NumberPool::SEED, pool.as_ref()], &luck_snake::ID).0
+}
+
+pub fn treasury_pda(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[Treasury::SEED, pool.as_ref()], &luck_snake::ID).0
+}
+
+pub fn withdrawal_log_pda(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[WithdrawalLog::SEED, pool.as_ref()], &luck_snake::ID).0
+}
+
+pub fn user_account_pda(pool: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[UserAccount::SEED, pool.as_ref(), user.as_ref()], &luck_snake::ID).0
+}
+
+pub fn vrf_request_pda(pool: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VrfRequest::SEED, pool.as_ref(), user.as_ref()], &luck_snake::ID).0
+}
+
+pub fn round_pda(pool: &Pubkey, round_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[Round::SEED, pool.as_ref(), &round_id.to_le_bytes()], &luck_snake::ID).0
+}
+
+pub fn oracle_request_pda(requester: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[mock_oracle::RandomnessRequest::SEED, requester.as_ref()], &mock_oracle::ID).0
+}
+
+/// 由Anchor生成的客户端结构构造指令
+pub fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: luck_snake::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// 由测试环境的付款人支付交易费用并发送交易
+/// 其他签名者的余额变化因此只包含指令本身的转账
+pub async fn process(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> std::result::Result<(), TransactionError> {
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    ctx.banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+/// 断言交易因指定的程序错误失败
+pub fn assert_error(result: std::result::Result<(), TransactionError>, error: LuckSnakeError) {
+    match result {
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, u32::from(error), "期望错误 {:?}", error);
+        }
+        other => panic!("期望错误 {:?}，实际结果 {:?}", error, other),
+    }
+}
+
+pub async fn lamports(ctx: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    ctx.banks_client.get_balance(*address).await.unwrap()
+}
+
+/// 读取并反序列化Anchor账户，账户不存在时返回None
+pub async fn fetch<T: AccountDeserialize>(ctx: &mut ProgramTestContext, address: &Pubkey) -> Option<T> {
+    ctx.banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .map(|account| T::try_deserialize(&mut account.data.as_slice()).unwrap())
+}
+
+/// 从付款人处转入lamports创建新用户
+pub async fn funded_user(ctx: &mut ProgramTestContext, lamports: u64) -> Keypair {
+    let user = Keypair::new();
+    let transfer = system_instruction::transfer(&ctx.payer.pubkey(), &user.pubkey(), lamports);
+    process(ctx, &[transfer], &[]).await.unwrap();
+    user
+}
+
+/// 初始化配置、设置预言机并创建测试数字池，管理员为测试环境的付款人
+pub async fn setup_pool(ctx: &mut ProgramTestContext) -> Pubkey {
+    let authority = ctx.payer.pubkey();
+    let config = config_pda();
+    let pool = pool_pda(POOL_ID);
+    
+    let initialize = instruction(
+        luck_snake::accounts::Initialize {
+            config,
+            authority,
+            system_program: system_program::ID,
+        },
+        luck_snake::instruction::Initialize {},
+    );
+    let set_oracle = instruction(
+        luck_snake::accounts::SetOracle { config, authority },
+        luck_snake::instruction::SetOracle {
+            oracle_program: mock_oracle::ID,
+            oracle_authority: mock_oracle::ORACLE_AUTHORITY,
+        },
+    );
+    let create_pool = instruction(
+        luck_snake::accounts::CreatePool {
+            config,
+            pool,
+            number_pool: number_pool_pda(&pool),
+            treasury: treasury_pda(&pool),
+            withdrawal_log: withdrawal_log_pda(&pool),
+            authority,
+            system_program: system_program::ID,
+        },
+        luck_snake::instruction::CreatePool {
+            pool_id: POOL_ID,
+            pool_authority: authority,
+            generation_price: GENERATION_PRICE,
+            range_min: RANGE_MIN,
+            range_max: RANGE_MAX,
+        },
+    );
+    process(ctx, &[initialize, set_oracle, create_pool], &[]).await.unwrap();
+    
+    pool
+}
+
+/// 为用户创建数字池下的用户账户
+pub async fn init_user(ctx: &mut ProgramTestContext, pool: &Pubkey, user: &Keypair) {
+    let init_user = instruction(
+        luck_snake::accounts::InitUser {
+            pool: *pool,
+            user_account: user_account_pda(pool, &user.pubkey()),
+            user: user.pubkey(),
+            system_program: system_program::ID,
+        },
+        luck_snake::instruction::InitUser {},
+    );
+    process(ctx, &[init_user], &[user]).await.unwrap();
+}
@@ -0,0 +1,366 @@
+//! 代币支付的集成测试：在本地创建SPL Token和Token-2022的mint，覆盖生成收费和提取代币资金
+//! 运行：cargo test -p luck_snake --features program-test
+
+#![cfg(feature = "program-test")]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::system_program;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::instruction::initialize_transfer_fee_config;
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+use anchor_spl::token_interface::TokenAccount;
+use common::*;
+use luck_snake::errors::LuckSnakeError;
+use luck_snake::state::*;
+use solana_keypair::Keypair;
+use solana_program_test::ProgramTestContext;
+use solana_signer::Signer;
+
+/// 代币价格（最小单位）
+const TOKEN_PRICE: u64 = 500;
+/// 代币精度
+const DECIMALS: u8 = 6;
+/// 转账手续费比例：1%
+const TRANSFER_FEE_BPS: u16 = 100;
+/// 1%手续费下财库实收TOKEN_PRICE时用户需要转出的数量：506 - ceil(506 * 1%) = 500
+const TOKEN_PRICE_WITH_FEE: u64 = 506;
+/// 铸造给用户的代币数量
+const USER_TOKENS: u64 = 1_000_000;
+
+/// 测试环境：已创建数字池和用户账户，数字池以新建的mint支付
+struct TokenFixture {
+    ctx: ProgramTestContext,
+    pool: Pubkey,
+    user: Keypair,
+    mint: Pubkey,
+    token_program: Pubkey,
+    user_token_account: Pubkey,
+    treasury_token_account: Pubkey,
+}
+
+/// 创建mint账户，带转账手续费时同时初始化TransferFeeConfig扩展
+async fn create_mint(ctx: &mut ProgramTestContext, token_program: &Pubkey, transfer_fee_bps: Option<u16>) -> Pubkey {
+    let mint = Keypair::new();
+    let authority = ctx.payer.pubkey();
+    let extensions: &[ExtensionType] = if transfer_fee_bps.is_some() {
+        &[ExtensionType::TransferFeeConfig]
+    } else {
+        &[]
+    };
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extensions).unwrap();
+    let rent = ctx.banks_client.get_rent().await.unwrap().minimum_balance(space);
+    
+    let mut instructions = vec![system_instruction::create_account(
+        &authority,
+        &mint.pubkey(),
+        rent,
+        space as u64,
+        token_program,
+    )];
+    if let Some(transfer_fee_bps) = transfer_fee_bps {
+        instructions.push(
+            initialize_transfer_fee_config(
+                token_program,
+                &mint.pubkey(),
+                Some(&authority),
+                Some(&authority),
+                transfer_fee_bps,
+                u64::MAX,
+            )
+            .unwrap(),
+        );
+    }
+    instructions.push(
+        spl_token_2022::instruction::initialize_mint2(token_program, &mint.pubkey(), &authority, None, DECIMALS)
+            .unwrap(),
+    );
+    process(ctx, &instructions, &[&mint]).await.unwrap();
+    
+    mint.pubkey()
+}
+
+/// 创建归owner所有的代币账户，mint带转账手续费时预留TransferFeeAmount扩展的空间
+async fn create_token_account(
+    ctx: &mut ProgramTestContext,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    with_transfer_fee: bool,
+) -> Pubkey {
+    let account = Keypair::new();
+    let extensions: &[ExtensionType] = if with_transfer_fee {
+        &[ExtensionType::TransferFeeAmount]
+    } else {
+        &[]
+    };
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(extensions).unwrap();
+    let rent = ctx.banks_client.get_rent().await.unwrap().minimum_balance(space);
+    
+    let instructions = [
+        system_instruction::create_account(&ctx.payer.pubkey(), &account.pubkey(), rent, space as u64, token_program),
+        spl_token_2022::instruction::initialize_account3(token_program, &account.pubkey(), mint, owner).unwrap(),
+    ];
+    process(ctx, &instructions, &[&account]).await.unwrap();
+    
+    account.pubkey()
+}
+
+async fn token_balance(ctx: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    fetch::<TokenAccount>(ctx, token_account).await.unwrap().amount
+}
+
+async fn token_fixture(token_program: Pubkey, transfer_fee_bps: Option<u16>) -> TokenFixture {
+    let mut ctx = start().await;
+    let pool = setup_pool(&mut ctx).await;
+    let user = funded_user(&mut ctx, 1_000_000_000).await;
+    init_user(&mut ctx, &pool, &user).await;
+    
+    let mint = create_mint(&mut ctx, &token_program, transfer_fee_bps).await;
+    let user_token_account =
+        create_token_account(&mut ctx, &token_program, &mint, &user.pubkey(), transfer_fee_bps.is_some()).await;
+    let mint_to = spl_token_2022::instruction::mint_to(
+        &token_program,
+        &mint,
+        &user_token_account,
+        &ctx.payer.pubkey(),
+        &[],
+        USER_TOKENS,
+    )
+    .unwrap();
+    process(&mut ctx, &[mint_to], &[]).await.unwrap();
+    
+    let treasury_token_account = Pubkey::find_program_address(
+        &[Pool::TREASURY_TOKEN_SEED, pool.as_ref(), mint.as_ref()],
+        &luck_snake::ID,
+    )
+    .0;
+    let set_payment_mint = instruction(
+        luck_snake::accounts::SetPaymentMint {
+            pool,
+            payment_mint: mint,
+            treasury_token_account,
+            authority: ctx.payer.pubkey(),
+            token_program,
+            system_program: system_program::ID,
+        },
+        luck_snake::instruction::SetPaymentMint { token_price: TOKEN_PRICE },
+    );
+    process(&mut ctx, &[set_payment_mint], &[]).await.unwrap();
+    
+    TokenFixture {
+        ctx,
+        pool,
+        user,
+        mint,
+        token_program,
+        user_token_account,
+        treasury_token_account,
+    }
+}
+
+/// 构造以代币支付的generate_number指令，with_token_accounts为false时省略代币账户
+fn generate_number_ix(fixture: &TokenFixture, with_token_accounts: bool, max_price: Option<u64>) -> Instruction {
+    let token_account = |address: Pubkey| with_token_accounts.then_some(address);
+    instruction(
+        luck_snake::accounts::GenerateNumber {
+            config: config_pda(),
+            pool: fixture.pool,
+            number_pool: number_pool_pda(&fixture.pool),
+            user_account: user_account_pda(&fixture.pool, &fixture.user.pubkey()),
+            user: fixture.user.pubkey(),
+            treasury: treasury_pda(&fixture.pool),
+            system_program: system_program::ID,
+            payment_mint: token_account(fixture.mint),
+            user_token_account: token_account(fixture.user_token_account),
+            treasury_token_account: token_account(fixture.treasury_token_account),
+            token_program: token_account(fixture.token_program),
+            referral_rewards: None,
+        },
+        luck_snake::instruction::GenerateNumber { max_price },
+    )
+}
+
+/// 构造数字池管理员把代币提取到recipient的withdraw_token_funds指令
+fn withdraw_token_funds_ix(fixture: &TokenFixture, recipient: Pubkey, amount: u64) -> Instruction {
+    instruction(
+        luck_snake::accounts::WithdrawTokenFunds {
+            config: config_pda(),
+            pool: fixture.pool,
+            payment_mint: fixture.mint,
+            treasury_token_account: fixture.treasury_token_account,
+            recipient_token_account: recipient,
+            authority: fixture.ctx.payer.pubkey(),
+            token_program: fixture.token_program,
+        },
+        luck_snake::instruction::WithdrawTokenFunds { amount },
+    )
+}
+
+#[tokio::test]
+async fn spl_token_payment_and_withdraw() {
+    let mut fixture = token_fixture(anchor_spl::token::ID, None).await;
+    let treasury = treasury_pda(&fixture.pool);
+    let treasury_lamports = lamports(&mut fixture.ctx, &treasury).await;
+    
+    let generate = generate_number_ix(&fixture, true, None);
+    process(&mut fixture.ctx, &[generate], &[&fixture.user]).await.unwrap();
+    
+    // 费用以代币收取，lamports财库不变
+    assert_eq!(token_balance(&mut fixture.ctx, &fixture.user_token_account).await, USER_TOKENS - TOKEN_PRICE);
+    assert_eq!(token_balance(&mut fixture.ctx, &fixture.treasury_token_account).await, TOKEN_PRICE);
+    assert_eq!(lamports(&mut fixture.ctx, &treasury).await, treasury_lamports);
+    let pool = fetch::<Pool>(&mut fixture.ctx, &fixture.pool).await.unwrap();
+    assert_eq!(pool.total_generated_numbers, 1);
+    
+    // 数字池管理员提取代币资金
+    let authority = fixture.ctx.payer.pubkey();
+    let recipient =
+        create_token_account(&mut fixture.ctx, &fixture.token_program, &fixture.mint, &authority, false).await;
+    let withdraw = withdraw_token_funds_ix(&fixture, recipient, TOKEN_PRICE);
+    process(&mut fixture.ctx, &[withdraw], &[]).await.unwrap();
+    
+    assert_eq!(token_balance(&mut fixture.ctx, &fixture.treasury_token_account).await, 0);
+    assert_eq!(token_balance(&mut fixture.ctx, &recipient).await, TOKEN_PRICE);
+}
+
+#[tokio::test]
+async fn token_2022_transfer_fee_paid_by_user() {
+    let mut fixture = token_fixture(anchor_spl::token_2022::ID, Some(TRANSFER_FEE_BPS)).await;
+    
+    let generate = generate_number_ix(&fixture, true, None);
+    process(&mut fixture.ctx, &[generate], &[&fixture.user]).await.unwrap();
+    
+    // 财库实收代币价格，转账手续费由用户额外承担
+    assert_eq!(
+        token_balance(&mut fixture.ctx, &fixture.user_token_account).await,
+        USER_TOKENS - TOKEN_PRICE_WITH_FEE
+    );
+    assert_eq!(token_balance(&mut fixture.ctx, &fixture.treasury_token_account).await, TOKEN_PRICE);
+}
+
+#[tokio::test]
+async fn token_2022_max_price_includes_transfer_fee() {
+    let mut fixture = token_fixture(anchor_spl::token_2022::ID, Some(TRANSFER_FEE_BPS)).await;
+    
+    // 用户实际转出含手续费的数量，只覆盖代币价格的max_price被拒绝
+    let generate = generate_number_ix(&fixture, true, Some(TOKEN_PRICE));
+    assert_error(
+        process(&mut fixture.ctx, &[generate], &[&fixture.user]).await,
+        LuckSnakeError::PriceExceedsMax,
+    );
+    assert_eq!(token_balance(&mut fixture.ctx, &fixture.user_token_account).await, USER_TOKENS);
+    
+    let generate = generate_number_ix(&fixture, true, Some(TOKEN_PRICE_WITH_FEE));
+    process(&mut fixture.ctx, &[generate], &[&fixture.user]).await.unwrap();
+    assert_eq!(
+        token_balance(&mut fixture.ctx, &fixture.user_token_account).await,
+        USER_TOKENS - TOKEN_PRICE_WITH_FEE
+    );
+}
+
+#[tokio::test]
+async fn token_payment_requires_token_accounts() {
+    let mut fixture = token_fixture(anchor_spl::token::ID, None).await;
+    
+    let generate = generate_number_ix(&fixture, false, None);
+    assert_error(
+        process(&mut fixture.ctx, &[generate], &[&fixture.user]).await,
+        LuckSnakeError::TokenPaymentAccountsMissing,
+    );
+    assert_eq!(token_balance(&mut fixture.ctx, &fixture.user_token_account).await, USER_TOKENS);
+}
+
+/// 构造由测试环境的付款人调用的distribute_tokens指令，recipient_token_accounts为接收方代币账户
+fn distribute_tokens_ix(fixture: &TokenFixture, recipient_token_accounts: &[Pubkey]) -> Instruction {
+    let mut distribute = instruction(
+        luck_snake::accounts::DistributeTokens {
+            config: config_pda(),
+            pool: fixture.pool,
+            payment_mint: fixture.mint,
+            treasury_token_account: fixture.treasury_token_account,
+            token_fee_accrual: Pubkey::find_program_address(
+                &[TokenFeeAccrual::SEED, fixture.pool.as_ref(), fixture.mint.as_ref()],
+                &luck_snake::ID,
+            )
+            .0,
+            payer: fixture.ctx.payer.pubkey(),
+            token_program: fixture.token_program,
+            system_program: system_program::ID,
+        },
+        luck_snake::instruction::DistributeTokens {},
+    );
+    distribute
+        .accounts
+        .extend(recipient_token_accounts.iter().map(|account| AccountMeta::new(*account, false)));
+    distribute
+}
+
+#[tokio::test]
+async fn token_revenue_distributed_by_fee_split() {
+    let mut fixture = token_fixture(anchor_spl::token::ID, None).await;
+    let generate = generate_number_ix(&fixture, true, None);
+    process(&mut fixture.ctx, &[generate], &[&fixture.user]).await.unwrap();
+    
+    let authority = fixture.ctx.payer.pubkey();
+    let recipient =
+        create_token_account(&mut fixture.ctx, &fixture.token_program, &fixture.mint, &authority, false).await;
+    let partner_a = Pubkey::new_unique();
+    let partner_b = Pubkey::new_unique();
+    let partner_a_tokens =
+        create_token_account(&mut fixture.ctx, &fixture.token_program, &fixture.mint, &partner_a, false).await;
+    let partner_b_tokens =
+        create_token_account(&mut fixture.ctx, &fixture.token_program, &fixture.mint, &partner_b, false).await;
+    let set_fee_split = |fee_recipients: Vec<FeeRecipient>| {
+        instruction(
+            luck_snake::accounts::SetFeeSplit { config: config_pda(), authority },
+            luck_snake::instruction::SetFeeSplit { fee_recipients },
+        )
+    };
+    let fee_split = vec![
+        FeeRecipient { recipient: partner_a, share_bps: 7_000 },
+        FeeRecipient { recipient: partner_b, share_bps: 3_000 },
+    ];
+    process(&mut fixture.ctx, &[set_fee_split(fee_split)], &[]).await.unwrap();
+    
+    // 设置分账表后代币资金不能由数字池管理员提取
+    let withdraw = withdraw_token_funds_ix(&fixture, recipient, TOKEN_PRICE);
+    assert_error(process(&mut fixture.ctx, &[withdraw], &[]).await, LuckSnakeError::FeeSplitActive);
+    
+    // 只传入A的代币账户：500按70%/30%记账，A立即到账，B的份额留在财库代币账户中
+    let distribute = distribute_tokens_ix(&fixture, &[partner_a_tokens]);
+    process(&mut fixture.ctx, &[distribute], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut fixture.ctx, &partner_a_tokens).await, 350);
+    assert_eq!(token_balance(&mut fixture.ctx, &fixture.treasury_token_account).await, 150);
+    assert_eq!(fetch::<LuckSnakeConfig>(&mut fixture.ctx, &config_pda()).await.unwrap().token_fee_accrual_count, 1);
+    
+    // B还有待支付的代币时不能取消或替换分账表
+    assert_error(
+        process(&mut fixture.ctx, &[set_fee_split(Vec::new())], &[]).await,
+        LuckSnakeError::UndistributedFees,
+    );
+    let replacement = vec![FeeRecipient { recipient: partner_b, share_bps: 10_000 }];
+    assert_error(
+        process(&mut fixture.ctx, &[set_fee_split(replacement)], &[]).await,
+        LuckSnakeError::UndistributedFees,
+    );
+    
+    // 待支付的代币不会被再次分配，支付给B后计数归零
+    let distribute = distribute_tokens_ix(&fixture, &[partner_a_tokens, partner_b_tokens]);
+    process(&mut fixture.ctx, &[distribute], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut fixture.ctx, &partner_a_tokens).await, 350);
+    assert_eq!(token_balance(&mut fixture.ctx, &partner_b_tokens).await, 150);
+    assert_eq!(token_balance(&mut fixture.ctx, &fixture.treasury_token_account).await, 0);
+    assert_eq!(fetch::<LuckSnakeConfig>(&mut fixture.ctx, &config_pda()).await.unwrap().token_fee_accrual_count, 0);
+    
+    // 全部支付后可以取消分账表，之后的代币收入恢复由数字池管理员提取
+    process(&mut fixture.ctx, &[set_fee_split(Vec::new())], &[]).await.unwrap();
+    let generate = generate_number_ix(&fixture, true, None);
+    process(&mut fixture.ctx, &[generate], &[&fixture.user]).await.unwrap();
+    let withdraw = withdraw_token_funds_ix(&fixture, recipient, TOKEN_PRICE);
+    process(&mut fixture.ctx, &[withdraw], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut fixture.ctx, &recipient).await, TOKEN_PRICE);
+}
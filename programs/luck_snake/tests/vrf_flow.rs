@@ -0,0 +1,220 @@
+//! 预言机请求、回填、完成和取消流程的超时边界测试
+//! 运行：cargo test -p luck_snake --features program-test
+
+#![cfg(feature = "program-test")]
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use common::*;
+use luck_snake::errors::LuckSnakeError;
+use luck_snake::state::*;
+use solana_keypair::Keypair;
+use solana_program_test::ProgramTestContext;
+use solana_signer::Signer;
+
+/// 预言机回填的随机数
+const RANDOMNESS: [u8; 32] = [7u8; 32];
+
+/// 测试环境：已创建数字池、用户账户，并由用户发起了一次预言机请求
+struct VrfFixture {
+    ctx: ProgramTestContext,
+    pool: Pubkey,
+    user: Keypair,
+    vrf_request: Pubkey,
+    request_slot: u64,
+}
+
+async fn request_fixture() -> VrfFixture {
+    let mut ctx = start().await;
+    let pool = setup_pool(&mut ctx).await;
+    let user = funded_user(&mut ctx, 1_000_000_000).await;
+    init_user(&mut ctx, &pool, &user).await;
+    
+    let vrf_request = vrf_request_pda(&pool, &user.pubkey());
+    let request = instruction(
+        luck_snake::accounts::RequestVrfGeneration {
+            config: config_pda(),
+            pool,
+            vrf_request,
+            oracle_request: oracle_request_pda(&vrf_request),
+            oracle_program: mock_oracle::ID,
+            user: user.pubkey(),
+            system_program: system_program::ID,
+        },
+        luck_snake::instruction::RequestVrfGeneration { max_price: None },
+    );
+    process(&mut ctx, &[request], &[&user]).await.unwrap();
+    
+    let request_slot = fetch::<VrfRequest>(&mut ctx, &vrf_request).await.unwrap().request_slot;
+    
+    VrfFixture { ctx, pool, user, vrf_request, request_slot }
+}
+
+/// 以固定授权密钥调用模拟预言机的fulfill_randomness，由模拟预言机CPI回调fulfill_vrf_generation
+async fn fulfill(fixture: &mut VrfFixture) -> std::result::Result<(), solana_transaction_error::TransactionError> {
+    let oracle_authority = oracle_authority();
+    let mut accounts = mock_oracle::accounts::FulfillRandomness {
+        authority: oracle_authority.pubkey(),
+        randomness_request: oracle_request_pda(&fixture.vrf_request),
+        callback_program: luck_snake::ID,
+    }
+    .to_account_metas(None);
+    // 回调指令中授权地址之后的账户
+    accounts.push(AccountMeta::new_readonly(config_pda(), false));
+    accounts.push(AccountMeta::new(fixture.vrf_request, false));
+    let fulfill = Instruction {
+        program_id: mock_oracle::ID,
+        accounts,
+        data: mock_oracle::instruction::FulfillRandomness { randomness: RANDOMNESS }.data(),
+    };
+    process(&mut fixture.ctx, &[fulfill], &[&oracle_authority]).await
+}
+
+async fn complete(fixture: &mut VrfFixture) -> std::result::Result<(), solana_transaction_error::TransactionError> {
+    let complete = instruction(
+        luck_snake::accounts::CompleteVrfGeneration {
+            config: config_pda(),
+            pool: fixture.pool,
+            number_pool: number_pool_pda(&fixture.pool),
+            vrf_request: fixture.vrf_request,
+            user_account: user_account_pda(&fixture.pool, &fixture.user.pubkey()),
+            user: fixture.user.pubkey(),
+            treasury: treasury_pda(&fixture.pool),
+            system_program: system_program::ID,
+        },
+        luck_snake::instruction::CompleteVrfGeneration {},
+    );
+    process(&mut fixture.ctx, &[complete], &[&fixture.user]).await
+}
+
+async fn cancel(fixture: &mut VrfFixture) -> std::result::Result<(), solana_transaction_error::TransactionError> {
+    let cancel = instruction(
+        luck_snake::accounts::CancelVrfGeneration {
+            config: config_pda(),
+            pool: fixture.pool,
+            vrf_request: fixture.vrf_request,
+            user: fixture.user.pubkey(),
+        },
+        luck_snake::instruction::CancelVrfGeneration {},
+    );
+    process(&mut fixture.ctx, &[cancel], &[&fixture.user]).await
+}
+
+#[tokio::test]
+async fn fulfill_at_timeout_boundary_then_complete() {
+    let mut fixture = request_fixture().await;
+    let treasury = treasury_pda(&fixture.pool);
+    let treasury_before = lamports(&mut fixture.ctx, &treasury).await;
+    
+    // 超时判断为严格大于，request_slot + TIMEOUT_SLOTS仍可回填
+    fixture.ctx.warp_to_slot(fixture.request_slot + VrfRequest::TIMEOUT_SLOTS).unwrap();
+    fulfill(&mut fixture).await.unwrap();
+    
+    let vrf_request = fetch::<VrfRequest>(&mut fixture.ctx, &fixture.vrf_request).await.unwrap();
+    assert!(vrf_request.fulfilled);
+    assert_eq!(vrf_request.randomness, RANDOMNESS);
+    
+    // 已回填的请求不能取消
+    assert_error(cancel(&mut fixture).await, LuckSnakeError::VrfRequestAlreadyFulfilled);
+    
+    // 回填后即使超过超时时间也可以完成生成
+    fixture.ctx.warp_to_slot(fixture.request_slot + VrfRequest::TIMEOUT_SLOTS + 10).unwrap();
+    complete(&mut fixture).await.unwrap();
+    
+    let user_account = fetch::<UserAccount>(
+        &mut fixture.ctx,
+        &user_account_pda(&fixture.pool, &fixture.user.pubkey()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(user_account.numbers.len(), 1);
+    assert!((RANGE_MIN..=RANGE_MAX).contains(&user_account.numbers[0]));
+    
+    let pool = fetch::<Pool>(&mut fixture.ctx, &fixture.pool).await.unwrap();
+    assert_eq!(pool.total_generated_numbers, 1);
+    assert_eq!(lamports(&mut fixture.ctx, &treasury).await, treasury_before + GENERATION_PRICE);
+    assert!(fetch::<VrfRequest>(&mut fixture.ctx, &fixture.vrf_request).await.is_none());
+}
+
+#[tokio::test]
+async fn fulfill_after_timeout_fails() {
+    let mut fixture = request_fixture().await;
+    
+    fixture.ctx.warp_to_slot(fixture.request_slot + VrfRequest::TIMEOUT_SLOTS + 1).unwrap();
+    assert_error(fulfill(&mut fixture).await, LuckSnakeError::VrfRequestTimedOut);
+    
+    let vrf_request = fetch::<VrfRequest>(&mut fixture.ctx, &fixture.vrf_request).await.unwrap();
+    assert!(!vrf_request.fulfilled);
+}
+
+#[tokio::test]
+async fn complete_before_fulfill_fails() {
+    let mut fixture = request_fixture().await;
+    
+    assert_error(complete(&mut fixture).await, LuckSnakeError::VrfRequestNotFulfilled);
+}
+
+#[tokio::test]
+async fn cancel_only_after_timeout() {
+    let mut fixture = request_fixture().await;
+    
+    // request_slot + TIMEOUT_SLOTS时尚未超时
+    fixture.ctx.warp_to_slot(fixture.request_slot + VrfRequest::TIMEOUT_SLOTS).unwrap();
+    assert_error(cancel(&mut fixture).await, LuckSnakeError::VrfRequestNotTimedOut);
+    
+    fixture.ctx.warp_to_slot(fixture.request_slot + VrfRequest::TIMEOUT_SLOTS + 1).unwrap();
+    let user_before = lamports(&mut fixture.ctx, &fixture.user.pubkey()).await;
+    let request_lamports = lamports(&mut fixture.ctx, &fixture.vrf_request).await;
+    cancel(&mut fixture).await.unwrap();
+    
+    // 暂存的费用和租金全部退还，交易费用由测试环境的付款人支付
+    assert_eq!(
+        lamports(&mut fixture.ctx, &fixture.user.pubkey()).await,
+        user_before + request_lamports
+    );
+    assert!(fetch::<VrfRequest>(&mut fixture.ctx, &fixture.vrf_request).await.is_none());
+    
+    // 取消后预言机迟到的回填被拒绝
+    assert!(fulfill(&mut fixture).await.is_err());
+}
+
+#[tokio::test]
+async fn cancel_fulfilled_request_after_round_closed() {
+    let mut fixture = request_fixture().await;
+    fulfill(&mut fixture).await.unwrap();
+    
+    // 回填之后管理员开启轮次，随后轮次截止，请求无法再完成
+    let mut clock = fixture.ctx.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let close_time = clock.unix_timestamp + 60;
+    let open_round = instruction(
+        luck_snake::accounts::OpenRound {
+            pool: fixture.pool,
+            round: round_pda(&fixture.pool, 0),
+            authority: fixture.ctx.payer.pubkey(),
+            system_program: system_program::ID,
+        },
+        luck_snake::instruction::OpenRound { close_time },
+    );
+    process(&mut fixture.ctx, &[open_round], &[]).await.unwrap();
+    
+    // 轮次截止之前请求仍可完成，不能取消
+    assert_error(cancel(&mut fixture).await, LuckSnakeError::VrfRequestAlreadyFulfilled);
+    
+    clock.unix_timestamp = close_time;
+    fixture.ctx.set_sysvar(&clock);
+    assert_error(complete(&mut fixture).await, LuckSnakeError::RoundClosed);
+    
+    let user_before = lamports(&mut fixture.ctx, &fixture.user.pubkey()).await;
+    let request_lamports = lamports(&mut fixture.ctx, &fixture.vrf_request).await;
+    cancel(&mut fixture).await.unwrap();
+    
+    // 已回填但无法完成的请求全额退款
+    assert_eq!(
+        lamports(&mut fixture.ctx, &fixture.user.pubkey()).await,
+        user_before + request_lamports
+    );
+    assert!(fetch::<VrfRequest>(&mut fixture.ctx, &fixture.vrf_request).await.is_none());
+}
@@ -0,0 +1,162 @@
+// Anchor 0.31.1在#[program]宏展开的IDL指令中仍调用已弃用的AccountInfo::realloc
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+
+declare_id!("CzFhBUPweJehuJUx4gzHRZHFmzacUasDdnvfZS9DxFbc");
+
+/// 固定的预言机授权地址
+/// 对应的私钥保存在tests/fixtures/mock_oracle_authority.json，仅用于本地测试
+pub const ORACLE_AUTHORITY: Pubkey = pubkey!("P89SJKCZrmKoa8NfXj2aGdQVoc7PVoUFZhf9u1GfR1d");
+
+/// 回填随机数时调用的消费方指令名
+pub const FULFILL_CALLBACK_IX: &str = "fulfill_vrf_generation";
+
+/// 模拟随机数预言机
+/// 记录消费方程序通过CPI发起的请求，并由固定授权地址签名回填随机数
+#[program]
+pub mod mock_oracle {
+    use super::*;
+    
+    /// 记录随机数请求
+    /// 由消费方程序通过CPI调用，请求方为消费方的PDA
+    /// 同一请求方再次请求时覆盖之前的记录（包括超时未回填的请求）
+    pub fn request_randomness(ctx: Context<RequestRandomness>, seed: [u8; 32]) -> Result<()> {
+        let request = &mut ctx.accounts.randomness_request;
+        request.requester = ctx.accounts.requester.key();
+        request.callback_program = *ctx.accounts.requester.owner;
+        request.seed = seed;
+        request.request_slot = Clock::get()?.slot;
+        request.fulfilled = false;
+        request.bump = ctx.bumps.randomness_request;
+        
+        msg!("收到随机数请求: {}", request.requester);
+        
+        Ok(())
+    }
+    
+    /// 回填随机数
+    /// 固定授权地址签名后，CPI调用消费方的回调指令
+    /// remaining_accounts按顺序转发给回调指令（授权地址之后的账户）
+    pub fn fulfill_randomness<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FulfillRandomness<'info>>,
+        randomness: [u8; 32],
+    ) -> Result<()> {
+        let request = &mut ctx.accounts.randomness_request;
+        require!(!request.fulfilled, MockOracleError::AlreadyFulfilled);
+        request.fulfilled = true;
+        
+        let mut data = hash::hash(format!("global:{}", FULFILL_CALLBACK_IX).as_bytes()).to_bytes()[..8].to_vec();
+        data.extend_from_slice(&randomness);
+        
+        let mut accounts = vec![AccountMeta::new_readonly(ctx.accounts.authority.key(), true)];
+        let mut account_infos = vec![ctx.accounts.authority.to_account_info()];
+        for account in ctx.remaining_accounts {
+            accounts.push(if account.is_writable {
+                AccountMeta::new(account.key(), account.is_signer)
+            } else {
+                AccountMeta::new_readonly(account.key(), account.is_signer)
+            });
+            account_infos.push(account.clone());
+        }
+        account_infos.push(ctx.accounts.callback_program.to_account_info());
+        
+        invoke(
+            &Instruction {
+                program_id: ctx.accounts.callback_program.key(),
+                accounts,
+                data,
+            },
+            &account_infos,
+        )?;
+        
+        msg!("回填随机数: {}", request.requester);
+        
+        Ok(())
+    }
+}
+
+/// 记录随机数请求的账户结构
+/// 账户顺序与luck_snake::oracle::REQUEST_RANDOMNESS_IX的约定一致
+#[derive(Accounts)]
+pub struct RequestRandomness<'info> {
+    /// 请求方，消费方程序的PDA
+    pub requester: Signer<'info>,
+    
+    /// 预言机请求账户
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = RandomnessRequest::LEN,
+        seeds = [RandomnessRequest::SEED, requester.key().as_ref()],
+        bump
+    )]
+    pub randomness_request: Account<'info, RandomnessRequest>,
+    
+    /// 付款人，支付请求账户的租金
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// 系统程序
+    pub system_program: Program<'info, System>,
+}
+
+/// 回填随机数的账户结构
+#[derive(Accounts)]
+pub struct FulfillRandomness<'info> {
+    /// 固定的预言机授权地址
+    #[account(address = ORACLE_AUTHORITY @ MockOracleError::Unauthorized)]
+    pub authority: Signer<'info>,
+    
+    /// 预言机请求账户
+    #[account(
+        mut,
+        seeds = [RandomnessRequest::SEED, randomness_request.requester.as_ref()],
+        bump = randomness_request.bump
+    )]
+    pub randomness_request: Account<'info, RandomnessRequest>,
+    
+    /// 消费方程序
+    /// CHECK: 通过address约束验证为发起请求的程序
+    #[account(executable, address = randomness_request.callback_program)]
+    pub callback_program: UncheckedAccount<'info>,
+}
+
+/// 预言机请求账户
+#[account]
+pub struct RandomnessRequest {
+    /// 请求方地址
+    pub requester: Pubkey,
+    /// 回调的消费方程序
+    pub callback_program: Pubkey,
+    /// 请求种子
+    pub seed: [u8; 32],
+    /// 请求时的slot
+    pub request_slot: u64,
+    /// 是否已回填
+    pub fulfilled: bool,
+    /// PDA bump种子
+    pub bump: u8,
+}
+
+impl RandomnessRequest {
+    /// 账户大小：8字节判别器 + 32字节请求方 + 32字节回调程序 + 32字节种子 + 8字节slot + 1字节标记 + 1字节bump
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 1 + 1;
+    /// PDA种子前缀
+    pub const SEED: &'static [u8] = b"request";
+}
+
+/// 模拟预言机错误类型
+#[error_code]
+pub enum MockOracleError {
+    /// 签名者不是固定的预言机授权地址
+    #[msg("Unauthorized")]
+    Unauthorized,
+    
+    /// 请求已经回填过
+    #[msg("Request already fulfilled")]
+    AlreadyFulfilled,
+}
@@ -1,8 +1,16 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { LuckSnake } from "../target/types/luck_snake";
+import { MockOracle } from "../target/types/mock_oracle";
 import { expect } from "chai";
-import { PublicKey, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  PublicKey,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+  SYSVAR_SLOT_HASHES_PUBKEY,
+} from "@solana/web3.js";
+import { keccak_256 } from "@noble/hashes/sha3";
+import oracleAuthoritySecret from "./fixtures/mock_oracle_authority.json";
 
 describe("luck_snake", () => {
   // 设置provider和program
@@ -11,7 +19,6 @@ describe("luck_snake", () => {
 
   const program = anchor.workspace.LuckSnake as Program<LuckSnake>;
   const authority = provider.wallet.publicKey;
-  const treasury = anchor.web3.Keypair.generate();
 
   // 计算配置账户的PDA地址
   const [configPda] = PublicKey.findProgramAddressSync(
@@ -19,6 +26,275 @@ describe("luck_snake", () => {
     program.programId
   );
 
+  // 计算数字池账户的PDA地址（种子为"pool"加上数字池编号的小端字节）
+  const poolPdaFor = (poolId: number): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("pool"), new anchor.BN(poolId).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  // 从数字池地址派生的账户：位图、财库和提取日志
+  const poolChildPda = (prefix: string, pool: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync([Buffer.from(prefix), pool.toBuffer()], program.programId)[0];
+
+  // 大部分测试使用的默认数字池
+  const POOL_ID = 0;
+  const poolPda = poolPdaFor(POOL_ID);
+  const numberPoolPda = poolChildPda("number_pool", poolPda);
+  const treasuryPda = poolChildPda("treasury", poolPda);
+  const withdrawalLogPda = poolChildPda("withdrawal_log", poolPda);
+
+  // 计算数字记录的PDA地址（种子为"number"前缀、数字池地址和数字的小端字节）
+  const numberRecordPda = (number: number, pool: PublicKey = poolPda): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("number"), pool.toBuffer(), new anchor.BN(number).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    )[0];
+
+  // 测试使用的数字范围，用于验证非零起点的换算
+  const RANGE_MIN = 1;
+  const RANGE_MAX = 1000;
+
+  // 在位图中查找第k个（从0开始）未发放的位置，与程序中的NumberPool::nth_free一致
+  const nthFree = (bitmap: number[], capacity: number, k: number): number => {
+    let remaining = k;
+    for (let index = 0; index < capacity; index++) {
+      if ((bitmap[index >> 3] & (1 << (index & 7))) === 0) {
+        if (remaining === 0) return index;
+        remaining--;
+      }
+    }
+    return -1;
+  };
+
+  // 计算用户账户的PDA地址（每个用户在每个数字池中各有一个账户）
+  const userAccountPda = (user: PublicKey, pool: PublicKey = poolPda): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), pool.toBuffer(), user.toBuffer()],
+      program.programId
+    )[0];
+
+  // 预测在指定slot连续生成count个数字的结果
+  // 与程序一致：种子为slot、用户nonce和完整的32字节用户地址，每次生成后nonce加1
+  const predictNumbers = (
+    user: PublicKey,
+    slot: number,
+    nonce: number,
+    count: number,
+    bitmap: number[],
+    capacity: number,
+    issued: number,
+    rangeMin: number
+  ): number[] => {
+    const working = bitmap.slice();
+    const numbers: number[] = [];
+    for (let i = 0; i < count; i++) {
+      const seed = Buffer.concat([
+        new anchor.BN(slot).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(nonce + i).toArrayLike(Buffer, "le", 8),
+        user.toBuffer(),
+      ]);
+      const randomValue = Buffer.from(keccak_256(seed)).readUInt32LE(0);
+      const index = nthFree(working, capacity, randomValue % (capacity - issued - i));
+      working[index >> 3] |= 1 << (index & 7);
+      numbers.push(rangeMin + index);
+    }
+    return numbers;
+  };
+
+  // 读取预测所需的链上状态：当前slot、位图、已发放数量和用户nonce
+  const generationState = async (user: PublicKey, pool: PublicKey = poolPda) => {
+    const slot = await provider.connection.getSlot();
+    const numberPool = await program.account.numberPool.fetch(poolChildPda("number_pool", pool));
+    const poolAccount = await program.account.pool.fetch(pool);
+    const userAccount = await program.account.userAccount.fetchNullable(userAccountPda(user, pool));
+    return {
+      slot,
+      bitmap: numberPool.bitmap as number[],
+      capacity: numberPool.capacity,
+      issued: poolAccount.totalGeneratedNumbers,
+      rangeMin: poolAccount.rangeMin,
+      nonce: userAccount ? userAccount.nonce.toNumber() : 0,
+    };
+  };
+
+  // 预测可能被选中数字的NumberRecord账户
+  // 交易可能在之后的几个slot中执行，因此为一段slot窗口内每个slot对应的数字都提供记录账户
+  const SLOT_WINDOW = 8;
+  const candidateRecords = async (
+    user: PublicKey,
+    count = 1,
+    pool: PublicKey = poolPda,
+    window = SLOT_WINDOW
+  ) => {
+    const state = await generationState(user, pool);
+    const keys = new Set<string>();
+    for (let s = state.slot; s < state.slot + window; s++) {
+      for (const number of predictNumbers(
+        user, s, state.nonce, count, state.bitmap, state.capacity, state.issued, state.rangeMin
+      )) {
+        keys.add(numberRecordPda(number, pool).toBase58());
+      }
+    }
+    return Array.from(keys).map((key) => ({
+      pubkey: new PublicKey(key),
+      isWritable: true,
+      isSigner: false,
+    }));
+  };
+
+  // 为随机生成时未创建记录的数字登记NumberRecord
+  const registerNumber = (user: anchor.web3.Keypair, number: number, pool: PublicKey = poolPda) =>
+    program.methods
+      .registerNumber(number)
+      .accounts({
+        pool: pool,
+        userAccount: userAccountPda(user.publicKey, pool),
+        numberRecord: numberRecordPda(number, pool),
+        owner: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+  // 为用户创建用户账户，生成数字之前必须先调用
+  const initUser = (user: anchor.web3.Keypair, pool: PublicKey = poolPda) =>
+    program.methods
+      .initUser()
+      .accounts({
+        pool: pool,
+        userAccount: userAccountPda(user.publicKey, pool),
+        user: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+  // 从交易日志中解析本程序发出的事件
+  const parseEvents = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    return Array.from(parser.parseLogs(tx.meta.logMessages));
+  };
+
+  // 创建数字池所需的账户
+  const createPoolAccounts = (pool: PublicKey) => ({
+    config: configPda,
+    pool: pool,
+    numberPool: poolChildPda("number_pool", pool),
+    treasury: poolChildPda("treasury", pool),
+    withdrawalLog: poolChildPda("withdrawal_log", pool),
+    authority: authority,
+    systemProgram: SystemProgram.programId,
+  });
+
+  // 测试用SPL代币：手动构造Token程序、Token-2022程序和关联代币账户程序的指令
+  const TOKEN_PROGRAM_ID = anchor.utils.token.TOKEN_PROGRAM_ID;
+  const TOKEN_2022_PROGRAM_ID = new PublicKey("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+  // Token-2022 mint扩展：账户大小和在InitializeMint2之前执行的初始化指令数据
+  type MintExtensions = { space: number; instructions: Buffer[] };
+  const NO_EXTENSIONS: MintExtensions = { space: 82, instructions: [] };
+
+  // 创建mint，铸造权限为全局管理员，不设冻结权限
+  const createTestMint = async (
+    decimals: number,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID,
+    extensions: MintExtensions = NO_EXTENSIONS
+  ): Promise<PublicKey> => {
+    const mint = anchor.web3.Keypair.generate();
+    const data = Buffer.alloc(35);
+    data.writeUInt8(20, 0); // InitializeMint2
+    data.writeUInt8(decimals, 1);
+    authority.toBuffer().copy(data, 2);
+    const mintInstruction = (instructionData: Buffer) =>
+      new anchor.web3.TransactionInstruction({
+        programId: tokenProgram,
+        keys: [{ pubkey: mint.publicKey, isSigner: false, isWritable: true }],
+        data: instructionData,
+      });
+    const tx = new anchor.web3.Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: authority,
+        newAccountPubkey: mint.publicKey,
+        space: extensions.space,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(extensions.space),
+        programId: tokenProgram,
+      }),
+      ...extensions.instructions.map(mintInstruction),
+      mintInstruction(data)
+    );
+    await provider.sendAndConfirm(tx, [mint]);
+    return mint.publicKey;
+  };
+
+  // 计算关联代币账户地址（种子为所有者、代币程序和mint）
+  const associatedTokenAddress = (
+    mint: PublicKey,
+    owner: PublicKey,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+  ): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [owner.toBuffer(), tokenProgram.toBuffer(), mint.toBuffer()],
+      anchor.utils.token.ASSOCIATED_PROGRAM_ID
+    )[0];
+
+  // 创建关联代币账户（已存在时不做修改）
+  const createTokenAccount = async (
+    mint: PublicKey,
+    owner: PublicKey,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+  ): Promise<PublicKey> => {
+    const address = associatedTokenAddress(mint, owner, tokenProgram);
+    const tx = new anchor.web3.Transaction().add(
+      new anchor.web3.TransactionInstruction({
+        programId: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        keys: [
+          { pubkey: authority, isSigner: true, isWritable: true },
+          { pubkey: address, isSigner: false, isWritable: true },
+          { pubkey: owner, isSigner: false, isWritable: false },
+          { pubkey: mint, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          { pubkey: tokenProgram, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([1]), // CreateIdempotent
+      })
+    );
+    await provider.sendAndConfirm(tx);
+    return address;
+  };
+
+  // 由全局管理员铸造代币到指定代币账户
+  const mintTokens = async (
+    mint: PublicKey,
+    destination: PublicKey,
+    amount: number,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+  ) => {
+    const data = Buffer.alloc(9);
+    data.writeUInt8(7, 0); // MintTo
+    data.writeBigUInt64LE(BigInt(amount), 1);
+    const tx = new anchor.web3.Transaction().add(
+      new anchor.web3.TransactionInstruction({
+        programId: tokenProgram,
+        keys: [
+          { pubkey: mint, isSigner: false, isWritable: true },
+          { pubkey: destination, isSigner: false, isWritable: true },
+          { pubkey: authority, isSigner: true, isWritable: false },
+        ],
+        data,
+      })
+    );
+    await provider.sendAndConfirm(tx);
+  };
+
+  // 读取代币账户余额（最小单位）
+  const tokenBalance = async (address: PublicKey): Promise<number> =>
+    Number((await provider.connection.getTokenAccountBalance(address)).value.amount);
+
   // 测试：初始化程序
   it("初始化程序", async () => {
     // 调用初始化指令
@@ -27,22 +303,72 @@ describe("luck_snake", () => {
       .accounts({
         config: configPda,                      // 配置账户PDA
         authority: authority,                    // 管理员账户
-        treasury: treasury.publicKey,           // 财库账户
         systemProgram: SystemProgram.programId, // 系统程序
       })
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
-    // 获取配置账户数据并验证
+    // 获取配置账户数据并验证管理员地址
     const config = await program.account.luckSnakeConfig.fetch(configPda);
-    
-    // 验证管理员地址
     expect(config.authority.toString()).to.equal(authority.toString());
-    // 验证财库地址
-    expect(config.treasury.toString()).to.equal(treasury.publicKey.toString());
+    expect(config.paused).to.be.false;
+
+    // 验证初始化事件
+    const events = await parseEvents(tx);
+    const initialized = events.find((event) => event.name === "configInitialized");
+    expect(initialized.data.authority.toString()).to.equal(authority.toString());
+  });
+
+  // 测试：无效的数字范围应被拒绝
+  it("拒绝无效的数字范围", async () => {
+    // 最小值大于最大值，或范围内的数字个数超过1000
+    for (const [rangeMin, rangeMax] of [[10, 5], [0, 1000]]) {
+      try {
+        await program.methods
+          .createPool(new anchor.BN(POOL_ID), authority, new anchor.BN(100000), rangeMin, rangeMax)
+          .accounts(createPoolAccounts(poolPda))
+          .rpc();
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidNumberRange");
+      }
+    }
+  });
+
+  // 测试：创建数字池
+  it("创建数字池", async () => {
+    const tx = await program.methods
+      .createPool(new anchor.BN(POOL_ID), authority, new anchor.BN(100000), RANGE_MIN, RANGE_MAX)
+      .accounts(createPoolAccounts(poolPda))
+      .rpc({ commitment: "confirmed" });
+
+    const pool = await program.account.pool.fetch(poolPda);
+    
+    // 验证数字池编号、管理员和财库地址
+    expect(pool.poolId.toNumber()).to.equal(POOL_ID);
+    expect(pool.authority.toString()).to.equal(authority.toString());
+    expect(pool.treasury.toString()).to.equal(treasuryPda.toString());
     // 验证生成价格（100,000 lamports）
-    expect(config.generationPrice.toNumber()).to.equal(100000);
+    expect(pool.generationPrice.toNumber()).to.equal(100000);
     // 验证初始计数为0
-    expect(config.totalGeneratedNumbers).to.equal(0);
+    expect(pool.totalGeneratedNumbers).to.equal(0);
+    // 验证数字范围
+    expect(pool.rangeMin).to.equal(RANGE_MIN);
+    expect(pool.rangeMax).to.equal(RANGE_MAX);
+
+    // 验证位图容量为1000且没有任何数字被发放
+    const numberPool = await program.account.numberPool.fetch(numberPoolPda);
+    expect(numberPool.capacity).to.equal(1000);
+    expect(numberPool.bitmap.every((byte: number) => byte === 0)).to.be.true;
+
+    // 验证创建事件
+    const events = await parseEvents(tx);
+    const created = events.find((event) => event.name === "poolCreated");
+    expect(created.data.pool.toString()).to.equal(poolPda.toString());
+    expect(created.data.authority.toString()).to.equal(authority.toString());
+    expect(created.data.treasury.toString()).to.equal(treasuryPda.toString());
+    expect(created.data.generationPrice.toNumber()).to.equal(100000);
+    expect(created.data.rangeMin).to.equal(RANGE_MIN);
+    expect(created.data.rangeMax).to.equal(RANGE_MAX);
   });
 
   // 测试：生成随机数字
@@ -59,39 +385,62 @@ describe("luck_snake", () => {
     // 等待充值确认
     await new Promise((resolve) => setTimeout(resolve, 1000));
 
+    // 创建用户账户
+    await initUser(user);
+
     // 计算用户账户的PDA地址
     const [userAccountPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user"), user.publicKey.toBuffer()],
+      [Buffer.from("user"), poolPda.toBuffer(), user.publicKey.toBuffer()],
       program.programId
     );
 
     // 调用生成数字指令
     const tx = await program.methods
-      .generateNumber()
+      .generateNumber(null)
       .accounts({
         config: configPda,                      // 配置账户
+        pool: poolPda,
+        numberPool: numberPoolPda,              // 已发放数字位图
         userAccount: userAccountPda,            // 用户账户PDA
         user: user.publicKey,                   // 用户钱包
-        treasury: treasury.publicKey,           // 财库账户
+        treasury: treasuryPda,           // 财库账户
         systemProgram: SystemProgram.programId, // 系统程序
       })
+      .remainingAccounts(await candidateRecords(user.publicKey)) // 候选数字记录
       .signers([user])  // 用户签名
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     // 获取用户账户数据并验证
     const userAccount = await program.account.userAccount.fetch(userAccountPda);
     
     // 验证用户拥有1个数字
     expect(userAccount.numbers.length).to.equal(1);
-    // 验证数字在0-999范围内
-    expect(userAccount.numbers[0]).to.be.gte(0);
-    expect(userAccount.numbers[0]).to.be.lte(999);
+    // 验证数字在配置的范围内
+    expect(userAccount.numbers[0]).to.be.gte(RANGE_MIN);
+    expect(userAccount.numbers[0]).to.be.lte(RANGE_MAX);
     // 验证nonce值增加到1
     expect(userAccount.nonce.toNumber()).to.equal(1);
 
-    // 验证全局计数器增加
-    const config = await program.account.luckSnakeConfig.fetch(configPda);
-    expect(config.totalGeneratedNumbers).to.equal(1);
+    // 验证数字记录已创建且所有者为该用户
+    const record = await program.account.numberRecord.fetch(
+      numberRecordPda(userAccount.numbers[0])
+    );
+    expect(record.number).to.equal(userAccount.numbers[0]);
+    expect(record.owner.toString()).to.equal(user.publicKey.toString());
+
+    // 验证数字池计数器增加
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.totalGeneratedNumbers).to.equal(1);
+
+    // 验证生成事件中的结构化字段
+    const events = await parseEvents(tx);
+    const generated = events.find((event) => event.name === "numberGenerated");
+    expect(generated.data.pool.toString()).to.equal(poolPda.toString());
+    expect(generated.data.number).to.equal(userAccount.numbers[0]);
+    expect(generated.data.owner.toString()).to.equal(user.publicKey.toString());
+    expect(generated.data.pricePaid.toNumber()).to.equal(100000);
+    expect(generated.data.totalGeneratedNumbers).to.equal(1);
+    expect(generated.data.userNumberCount).to.equal(1);
   });
 
   // 测试：为同一用户生成多个唯一数字
@@ -107,23 +456,29 @@ describe("luck_snake", () => {
     
     await new Promise((resolve) => setTimeout(resolve, 1000));
 
+    // 创建用户账户
+    await initUser(user);
+
     // 计算用户账户PDA
     const [userAccountPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user"), user.publicKey.toBuffer()],
+      [Buffer.from("user"), poolPda.toBuffer(), user.publicKey.toBuffer()],
       program.programId
     );
 
     // 生成3个数字
     for (let i = 0; i < 3; i++) {
       await program.methods
-        .generateNumber()
+        .generateNumber(null)
         .accounts({
           config: configPda,
+          pool: poolPda,
+          numberPool: numberPoolPda,
           userAccount: userAccountPda,
           user: user.publicKey,
-          treasury: treasury.publicKey,
+          treasury: treasuryPda,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(await candidateRecords(user.publicKey))
         .signers([user])
         .rpc();
     }
@@ -139,68 +494,2707 @@ describe("luck_snake", () => {
     expect(uniqueNumbers.size).to.equal(3);
   });
 
-  // 测试：提取资金
-  it("提取资金", async () => {
-    // 先给财库充值一些资金
-    await provider.connection.requestAirdrop(
-      treasury.publicKey,
-      1 * LAMPORTS_PER_SOL
+  // 测试：批量生成
+  it("批量生成最多MAX_BATCH_COUNT个数字，最坏情况下计算单元仍在默认预算之内", async () => {
+    // 与程序中的Pool::MAX_BATCH_COUNT和Pool::MAX_NUMBERS一致
+    const MAX_BATCH_COUNT = 10;
+    const MAX_NUMBERS = 1000;
+    // 最坏情况的计算单元至少比默认的200,000低这么多
+    const COMPUTE_UNIT_MARGIN = 20_000;
+    const batchPoolPda = poolPdaFor(10);
+    const batchTreasury = poolChildPda("treasury", batchPoolPda);
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 5 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    // 容量最大的数字池，位图扫描的字节数最多
+    await program.methods
+      .createPool(new anchor.BN(10), authority, new anchor.BN(1000), 0, MAX_NUMBERS - 1)
+      .accounts(createPoolAccounts(batchPoolPda))
+      .rpc();
+    await initUser(user, batchPoolPda);
+
+    const generateNumbers = (count: number, remaining: anchor.web3.AccountMeta[]) =>
+      program.methods
+        .generateNumbers(count, null)
+        .accounts({
+          config: configPda,
+          pool: batchPoolPda,
+          numberPool: poolChildPda("number_pool", batchPoolPda),
+          userAccount: userAccountPda(user.publicKey, batchPoolPda),
+          user: user.publicKey,
+          treasury: batchTreasury,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remaining)
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+    // 数量为0或超过上限应失败
+    for (const count of [0, MAX_BATCH_COUNT + 1]) {
+      try {
+        await generateNumbers(count, []);
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidBatchCount");
+      }
+    }
+
+    // 不带记录账户发放到只剩MAX_BATCH_COUNT个空闲数字，用户账户中的数字越多，反序列化和扩容的开销越大
+    for (let issued = 0; issued < MAX_NUMBERS - MAX_BATCH_COUNT; issued += MAX_BATCH_COUNT) {
+      await generateNumbers(MAX_BATCH_COUNT, []);
+    }
+
+    // 最后一批必然发放全部剩余数字，与执行的slot无关，因此可以为每个数字都提供记录账户，每条记录都被创建
+    const bitmap = (await program.account.numberPool.fetch(poolChildPda("number_pool", batchPoolPda)))
+      .bitmap as number[];
+    const remainingNumbers = [...Array(MAX_NUMBERS).keys()].filter(
+      (index) => (bitmap[index >> 3] & (1 << (index & 7))) === 0
     );
+    expect(remainingNumbers.length).to.equal(MAX_BATCH_COUNT);
+    const treasuryBefore = await provider.connection.getBalance(batchTreasury);
+    const signature = await generateNumbers(
+      MAX_BATCH_COUNT,
+      remainingNumbers.map((number) => ({
+        pubkey: numberRecordPda(number, batchPoolPda),
+        isWritable: true,
+        isSigner: false,
+      }))
+    );
+
+    // 一笔交易发放全部剩余数字，费用一次转入财库
+    const userAccount = await program.account.userAccount.fetch(userAccountPda(user.publicKey, batchPoolPda));
+    expect(userAccount.numbers.length).to.equal(MAX_NUMBERS);
+    expect(new Set(userAccount.numbers).size).to.equal(MAX_NUMBERS);
+    expect(userAccount.nonce.toNumber()).to.equal(MAX_NUMBERS);
+    expect(userAccount.numbers.slice(-MAX_BATCH_COUNT).sort((a, b) => a - b)).to.deep.equal(remainingNumbers);
+    const treasuryAfter = await provider.connection.getBalance(batchTreasury);
+    expect(treasuryAfter - treasuryBefore).to.equal(MAX_BATCH_COUNT * 1000);
+    for (const number of remainingNumbers) {
+      const record = await program.account.numberRecord.fetch(numberRecordPda(number, batchPoolPda));
+      expect(record.owner.toString()).to.equal(user.publicKey.toString());
+    }
+
+    // 创建全部记录并扩容最大用户账户的最大批量，计算单元仍低于默认的200,000，无需额外申请计算预算
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    expect(tx.meta.computeUnitsConsumed).to.be.lte(200_000 - COMPUTE_UNIT_MARGIN);
+  });
+
+  // 测试：购买指定数字
+  it("按价格倍数购买指定数字，已被占用或超出范围时失败", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
     await new Promise((resolve) => setTimeout(resolve, 1000));
-    
-    // 创建接收方账户
+    await initUser(user);
+
+    // 价格倍数不能低于1倍或超过10倍
+    for (const premium of [9999, 100001]) {
+      try {
+        await program.methods
+          .updatePool(null, null, premium)
+          .accounts({ pool: poolPda, authority: authority })
+          .rpc();
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidSpecificNumberPremium");
+      }
+    }
+
+    // 指定数字按生成价格的2倍收费
+    await program.methods
+      .updatePool(null, null, 20000)
+      .accounts({ pool: poolPda, authority: authority })
+      .rpc();
+
+    const claimSpecific = (number: number) =>
+      program.methods
+        .claimSpecificNumber(number, null)
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          numberPool: numberPoolPda,
+          numberRecord: numberRecordPda(number),
+          userAccount: userAccountPda(user.publicKey),
+          user: user.publicKey,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+    // 从位图中找一个尚未发放的数字
+    const state = await generationState(user.publicKey);
+    const number = RANGE_MIN + nthFree(state.bitmap, state.capacity, 0);
+
+    const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+    const signature = await claimSpecific(number);
+    const treasuryAfter = await provider.connection.getBalance(treasuryPda);
+    expect(treasuryAfter - treasuryBefore).to.equal(200000);
+
+    // 与随机生成一样记录所有权、更新位图和计数
+    const record = await program.account.numberRecord.fetch(numberRecordPda(number));
+    expect(record.owner.toString()).to.equal(user.publicKey.toString());
+    const userAccount = await program.account.userAccount.fetch(userAccountPda(user.publicKey));
+    expect(userAccount.numbers).to.deep.equal([number]);
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.totalGeneratedNumbers).to.equal(state.issued + 1);
+    const events = await parseEvents(signature);
+    const generated = events.find((event) => event.name === "numberGenerated");
+    expect(generated.data.number).to.equal(number);
+    expect(generated.data.pricePaid.toNumber()).to.equal(200000);
+
+    // 已被占用的数字不能再次购买
+    try {
+      await claimSpecific(number);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NumberAlreadyGenerated");
+    }
+
+    // 超出数字池范围的数字不能购买
+    for (const outOfRange of [RANGE_MIN - 1, RANGE_MAX + 1]) {
+      try {
+        await claimSpecific(outOfRange);
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidNumberRange");
+      }
+    }
+
+    // 恢复为不加价
+    await program.methods
+      .updatePool(null, null, 10000)
+      .accounts({ pool: poolPda, authority: authority })
+      .rpc();
+  });
+
+  // 测试：转移数字
+  it("转移数字到其他钱包，发送方账户缩小，接收方账户按需创建", async () => {
+    const sender = anchor.web3.Keypair.generate();
     const recipient = anchor.web3.Keypair.generate();
-    
-    // 获取财库余额（提取前）
-    const treasuryBalanceBefore = await provider.connection.getBalance(treasury.publicKey);
-    
-    // 调用提取资金指令
-    const tx = await program.methods
-      .withdrawFunds()
+    await provider.connection.requestAirdrop(sender.publicKey, 2 * LAMPORTS_PER_SOL);
+    await provider.connection.requestAirdrop(recipient.publicKey, LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(sender);
+
+    for (let i = 0; i < 2; i++) {
+      await program.methods
+        .generateNumber(null)
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          numberPool: numberPoolPda,
+          userAccount: userAccountPda(sender.publicKey),
+          user: sender.publicKey,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await candidateRecords(sender.publicKey))
+        .signers([sender])
+        .rpc();
+    }
+    const [number, kept] = (await program.account.userAccount.fetch(userAccountPda(sender.publicKey))).numbers;
+
+    const transfer = (from: anchor.web3.Keypair, to: PublicKey, n: number) =>
+      program.methods
+        .transferNumber(n, to)
+        .accounts({
+          pool: poolPda,
+          numberRecord: numberRecordPda(n),
+          senderAccount: userAccountPda(from.publicKey),
+          recipientAccount: userAccountPda(to),
+          sender: from.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([from])
+        .rpc({ commitment: "confirmed" });
+
+    // 不能转给自己，也不能转给默认地址
+    for (const to of [sender.publicKey, PublicKey.default]) {
+      try {
+        await transfer(sender, to, number);
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidTransferRecipient");
+      }
+    }
+
+    // 接收方还没有用户账户，由发送方创建
+    expect(await provider.connection.getAccountInfo(userAccountPda(recipient.publicKey))).to.be.null;
+    const sizeBefore = (await provider.connection.getAccountInfo(userAccountPda(sender.publicKey))).data.length;
+    const signature = await transfer(sender, recipient.publicKey, number);
+
+    // 双方的数字列表都已更新；随机生成的数字不一定有数字记录，不需要先登记，有记录时所有者同步更新
+    if (await provider.connection.getAccountInfo(numberRecordPda(number))) {
+      const record = await program.account.numberRecord.fetch(numberRecordPda(number));
+      expect(record.owner.toString()).to.equal(recipient.publicKey.toString());
+    }
+    const senderAccount = await program.account.userAccount.fetch(userAccountPda(sender.publicKey));
+    expect(senderAccount.numbers).to.deep.equal([kept]);
+    const recipientAccount = await program.account.userAccount.fetch(userAccountPda(recipient.publicKey));
+    expect(recipientAccount.user.toString()).to.equal(recipient.publicKey.toString());
+    expect(recipientAccount.numbers).to.deep.equal([number]);
+
+    // 发送方账户缩小一个数字的空间，租金随之减少
+    const senderInfo = await provider.connection.getAccountInfo(userAccountPda(sender.publicKey));
+    expect(senderInfo.data.length).to.equal(sizeBefore - 4);
+    expect(senderInfo.lamports).to.equal(
+      await provider.connection.getMinimumBalanceForRentExemption(sizeBefore - 4)
+    );
+
+    const events = await parseEvents(signature);
+    const transferred = events.find((event) => event.name === "numberTransferred");
+    expect(transferred.data.number).to.equal(number);
+    expect(transferred.data.from.toString()).to.equal(sender.publicKey.toString());
+    expect(transferred.data.to.toString()).to.equal(recipient.publicKey.toString());
+
+    // 原所有者不能再转移该数字
+    try {
+      await transfer(sender, recipient.publicKey, number);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // 转回已有用户账户的原所有者
+    await transfer(recipient, sender.publicKey, number);
+    const senderAfter = await program.account.userAccount.fetch(userAccountPda(sender.publicKey));
+    expect(senderAfter.numbers).to.deep.equal([kept, number]);
+    const recipientAfter = await program.account.userAccount.fetch(userAccountPda(recipient.publicKey));
+    expect(recipientAfter.numbers).to.deep.equal([]);
+  });
+
+  // 测试：代币化数字
+  it("代币化数字，铸造供应量为1的代币后撤销铸造权限，数字不能再直接转移", async () => {
+    const owner = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(owner.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(owner);
+
+    await program.methods
+      .generateNumber(null)
       .accounts({
-        config: configPda,                      // 配置账户
-        authority: authority,                    // 管理员账户
-        treasury: treasury.publicKey,           // 财库账户
-        recipient: recipient.publicKey,         // 接收方账户
-        systemProgram: SystemProgram.programId, // 系统程序
+        config: configPda,
+        pool: poolPda,
+        numberPool: numberPoolPda,
+        userAccount: userAccountPda(owner.publicKey),
+        user: owner.publicKey,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
       })
-      .signers([treasury])  // 财库需要签名
+      .remainingAccounts(await candidateRecords(owner.publicKey))
+      .signers([owner])
       .rpc();
+    const [number] = (await program.account.userAccount.fetch(userAccountPda(owner.publicKey))).numbers;
+    const numberBytes = Buffer.alloc(4);
+    numberBytes.writeUInt32LE(number);
 
-    // 验证接收方收到资金
-    const recipientBalance = await provider.connection.getBalance(recipient.publicKey);
-    expect(recipientBalance).to.be.gt(0);
-    
-    // 验证财库余额变为0
-    const treasuryBalanceAfter = await provider.connection.getBalance(treasury.publicKey);
-    expect(treasuryBalanceAfter).to.equal(0);
+    const [mint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("number_mint"), poolPda.toBuffer(), numberBytes],
+      program.programId
+    );
+    const [numberTokenPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("number_token"), mint.toBuffer()],
+      program.programId
+    );
+    const tokenAccount = anchor.utils.token.associatedAddress({ mint, owner: owner.publicKey });
+
+    const signature = await program.methods
+      .tokenizeNumber(number)
+      .accounts({
+        pool: poolPda,
+        numberRecord: numberRecordPda(number),
+        userAccount: userAccountPda(owner.publicKey),
+        mint,
+        tokenAccount,
+        numberToken: numberTokenPda,
+        owner: owner.publicKey,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+
+    // mint供应量为1、没有小数位，铸造权限已撤销
+    const mintInfo = (await provider.connection.getParsedAccountInfo(mint)).value.data as anchor.web3.ParsedAccountData;
+    expect(mintInfo.parsed.info.supply).to.equal("1");
+    expect(mintInfo.parsed.info.decimals).to.equal(0);
+    expect(mintInfo.parsed.info.mintAuthority).to.be.null;
+    const balance = await provider.connection.getTokenAccountBalance(tokenAccount);
+    expect(balance.value.amount).to.equal("1");
+
+    // 数字移出用户账户，记录的所有者改为mint地址
+    const userAccount = await program.account.userAccount.fetch(userAccountPda(owner.publicKey));
+    expect(userAccount.numbers).to.deep.equal([]);
+    const record = await program.account.numberRecord.fetch(numberRecordPda(number));
+    expect(record.owner.toString()).to.equal(mint.toString());
+
+    const numberToken = await program.account.numberToken.fetch(numberTokenPda);
+    expect(numberToken.pool.toString()).to.equal(poolPda.toString());
+    expect(numberToken.mint.toString()).to.equal(mint.toString());
+    expect(numberToken.number).to.equal(number);
+    expect(numberToken.generatedAt.toString()).to.equal(record.generatedAt.toString());
+
+    const events = await parseEvents(signature);
+    const tokenized = events.find((event) => event.name === "numberTokenized");
+    expect(tokenized.data.number).to.equal(number);
+    expect(tokenized.data.mint.toString()).to.equal(mint.toString());
+    expect(tokenized.data.owner.toString()).to.equal(owner.publicKey.toString());
+
+    // 代币化之后只能通过转移代币来转移数字
+    try {
+      await program.methods
+        .transferNumber(number, provider.wallet.publicKey)
+        .accounts({
+          pool: poolPda,
+          numberRecord: numberRecordPda(number),
+          senderAccount: userAccountPda(owner.publicKey),
+          recipientAccount: userAccountPda(provider.wallet.publicKey),
+          sender: owner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
   });
 
-  // 测试：非管理员尝试提取资金应失败
-  it("非管理员尝试提取资金应失败", async () => {
-    // 创建非管理员账户
-    const nonAuthority = anchor.web3.Keypair.generate();
-    const recipient = anchor.web3.Keypair.generate();
-    
+  // 测试：同一slot内两次生成使用不同的nonce
+  it("同一slot内两次生成得到不同的数字", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(user);
+
+    const before = await generationState(user.publicKey);
+    const remaining = await candidateRecords(user.publicKey, 2);
+
+    // 两条生成指令放在同一笔交易中，保证在同一个slot执行
+    const buildInstruction = () =>
+      program.methods
+        .generateNumber(null)
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          numberPool: numberPoolPda,
+          userAccount: userAccountPda(user.publicKey),
+          user: user.publicKey,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remaining)
+        .instruction();
+    const tx = new anchor.web3.Transaction().add(
+      await buildInstruction(),
+      await buildInstruction()
+    );
+    const signature = await provider.sendAndConfirm(tx, [user]);
+
+    const userAccount = await program.account.userAccount.fetch(userAccountPda(user.publicKey));
+    expect(userAccount.numbers.length).to.equal(2);
+    expect(userAccount.nonce.toNumber()).to.equal(2);
+    expect(userAccount.numbers[0]).to.not.equal(userAccount.numbers[1]);
+
+    // 两个数字分别来自nonce为0和1的种子
+    const { slot } = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const expected = predictNumbers(
+      user.publicKey, slot, 0, 2, before.bitmap, before.capacity, before.issued, before.rangeMin
+    );
+    expect(userAccount.numbers).to.deep.equal(expected);
+  });
+
+  // 测试：不同用户之间的数字全局唯一
+  it("不同用户获得的数字全局唯一", async () => {
+    const issued: number[] = [];
+
+    for (let i = 0; i < 3; i++) {
+      const user = anchor.web3.Keypair.generate();
+      await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await initUser(user);
+
+      const [userAccountPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("user"), poolPda.toBuffer(), user.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .generateNumber(null)
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          numberPool: numberPoolPda,
+          userAccount: userAccountPda,
+          user: user.publicKey,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await candidateRecords(user.publicKey))
+        .signers([user])
+        .rpc();
+
+      const userAccount = await program.account.userAccount.fetch(userAccountPda);
+      const number = userAccount.numbers[0];
+
+      // 每个数字的记录都指向唯一的所有者
+      const record = await program.account.numberRecord.fetch(numberRecordPda(number));
+      expect(record.owner.toString()).to.equal(user.publicKey.toString());
+      issued.push(number);
+    }
+
+    expect(new Set(issued).size).to.equal(issued.length);
+
+    // 所有已发放的数字都在位图中标记
+    const pool = await program.account.numberPool.fetch(numberPoolPda);
+    for (const number of issued) {
+      const index = number - RANGE_MIN;
+      expect(pool.bitmap[index >> 3] & (1 << (index & 7))).to.not.equal(0);
+    }
+  });
+
+  // 测试：未提供候选数字记录时仍然发放数字，之后登记记录
+  it("未提供候选数字记录时仍发放数字，所有者之后登记数字记录", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(user);
+
+    const [userAccountPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), poolPda.toBuffer(), user.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .generateNumber(null)
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        numberPool: numberPoolPda,
+        userAccount: userAccountPda,
+        user: user.publicKey,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    // 数字已发放到用户账户并在位图中标记，但还没有数字记录
+    const [number] = (await program.account.userAccount.fetch(userAccountPda)).numbers;
+    const index = number - RANGE_MIN;
+    const bitmap = (await program.account.numberPool.fetch(numberPoolPda)).bitmap;
+    expect(bitmap[index >> 3] & (1 << (index & 7))).to.not.equal(0);
+    expect(await provider.connection.getAccountInfo(numberRecordPda(number))).to.be.null;
+
+    // 不持有该数字的钱包不能登记
+    const stranger = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(stranger);
+    try {
+      await registerNumber(stranger, number);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NumberNotInUserAccount");
+    }
+
+    // 所有者登记后记录指向所有者，不能重复登记
+    const registered = (await parseEvents(await registerNumber(user, number))).find(
+      (event) => event.name === "numberRegistered"
+    );
+    expect(registered.data.number).to.equal(number);
+    expect(registered.data.owner.toString()).to.equal(user.publicKey.toString());
+    expect(registered.data.numberRecord.toString()).to.equal(numberRecordPda(number).toString());
+    const record = await program.account.numberRecord.fetch(numberRecordPda(number));
+    expect(record.owner.toString()).to.equal(user.publicKey.toString());
+    try {
+      await registerNumber(user, number);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NumberAlreadyGenerated");
+    }
+  });
+
+  // 测试：关闭用户账户
+  it("用户不再持有数字后可以关闭用户账户", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(user);
+
+    const balanceBefore = await provider.connection.getBalance(user.publicKey);
+    const signature = await program.methods
+      .closeUserAccount()
+      .accounts({
+        userAccount: userAccountPda(user.publicKey),
+        user: user.publicKey,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+    // 账户被关闭，租金退还给用户
+    expect(await provider.connection.getAccountInfo(userAccountPda(user.publicKey))).to.be.null;
+    const closed = (await parseEvents(signature)).find((event) => event.name === "userAccountClosed");
+    expect(closed.data.pool.toString()).to.equal(poolPda.toString());
+    expect(closed.data.user.toString()).to.equal(user.publicKey.toString());
+    expect(closed.data.userAccount.toString()).to.equal(userAccountPda(user.publicKey).toString());
+    const balanceAfter = await provider.connection.getBalance(user.publicKey);
+    expect(balanceAfter).to.be.gt(balanceBefore);
+
+    // 没有用户账户时无法生成数字
     try {
-      // 尝试以非管理员身份提取资金
       await program.methods
-        .withdrawFunds()
+        .generateNumber(null)
         .accounts({
           config: configPda,
-          authority: nonAuthority.publicKey,    // 使用非管理员账户
-          treasury: treasury.publicKey,
-          recipient: recipient.publicKey,
+          pool: poolPda,
+          numberPool: numberPoolPda,
+          userAccount: userAccountPda(user.publicKey),
+          user: user.publicKey,
+          treasury: treasuryPda,
           systemProgram: SystemProgram.programId,
         })
-        .signers([nonAuthority, treasury])  // 财库也需要签名
+        .remainingAccounts(await candidateRecords(user.publicKey))
+        .signers([user])
         .rpc();
-      
-      // 如果没有抛出错误，测试失败
       expect.fail("应该抛出错误");
     } catch (error) {
-      // 验证错误信息包含约束失败（can use "constraint" or check for specific error codes）
-      expect(error.toString()).to.include("ConstraintHasOne");
+      expect(error.toString()).to.include("AccountNotInitialized");
     }
   });
-});
\ No newline at end of file
+
+  // 测试：仍持有数字时不能关闭用户账户
+  it("仍持有数字时无法关闭用户账户", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(user);
+
+    await program.methods
+      .generateNumber(null)
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        numberPool: numberPoolPda,
+        userAccount: userAccountPda(user.publicKey),
+        user: user.publicKey,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(await candidateRecords(user.publicKey))
+      .signers([user])
+      .rpc();
+
+    try {
+      await program.methods
+        .closeUserAccount()
+        .accounts({
+          userAccount: userAccountPda(user.publicKey),
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("UserAccountNotEmpty");
+    }
+  });
+
+  // 计算待揭示请求账户的PDA地址
+  const pendingPda = (user: PublicKey, pool: PublicKey = poolPda): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("pending"), pool.toBuffer(), user.toBuffer()],
+      program.programId
+    )[0];
+
+  // 从SlotHashes sysvar中读取slot不早于targetSlot的最早一条区块哈希
+  // 数据格式：8字节条数，随后每条为8字节slot和32字节哈希，按slot降序排列
+  const slotHashAtOrAfter = async (targetSlot: number): Promise<Buffer> => {
+    const info = await provider.connection.getAccountInfo(SYSVAR_SLOT_HASHES_PUBKEY);
+    const count = Number(info.data.readBigUInt64LE(0));
+    let found: Buffer = null;
+    for (let i = 0; i < count; i++) {
+      const offset = 8 + i * 40;
+      if (Number(info.data.readBigUInt64LE(offset)) < targetSlot) break;
+      found = info.data.subarray(offset + 8, offset + 40);
+    }
+    return found;
+  };
+
+  // 等待链上slot超过指定值
+  const waitForSlot = async (slot: number) => {
+    while ((await provider.connection.getSlot()) <= slot) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+  };
+
+  // 提交-揭示模式下提交到揭示之间的slot数，与程序中的PendingGeneration::REVEAL_DELAY_SLOTS一致
+  const REVEAL_DELAY_SLOTS = 2;
+
+  // 承诺的有效期，与程序中的PendingGeneration::EXPIRY_SLOTS一致
+  const COMMITMENT_EXPIRY_SLOTS = 400;
+
+  // 测试：提交-揭示模式生成数字
+  it("提交-揭示模式生成数字", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(user);
+
+    // 提交秘密值的哈希承诺
+    const secret = anchor.web3.Keypair.generate().publicKey.toBuffer();
+    const commitment = Array.from(keccak_256(secret));
+    const commitSignature = await program.methods
+      .commitGeneration(commitment, null)
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        pending: pendingPda(user.publicKey),
+        user: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+    // 生成费用暂存在请求账户中
+    const pending = await program.account.pendingGeneration.fetch(pendingPda(user.publicKey));
+    expect(pending.amount.toNumber()).to.equal(100000);
+    const committed = (await parseEvents(commitSignature)).find((event) => event.name === "generationCommitted");
+    expect(committed.data.user.toString()).to.equal(user.publicKey.toString());
+    expect(committed.data.amount.toNumber()).to.equal(100000);
+    expect(committed.data.revealSlot.toNumber()).to.equal(pending.commitSlot.toNumber() + REVEAL_DELAY_SLOTS);
+
+    // 等待揭示延迟后，所有输入都已确定，可以算出被选中的数字
+    const revealSlot = pending.commitSlot.toNumber() + REVEAL_DELAY_SLOTS;
+    await waitForSlot(revealSlot);
+    const slotHash = await slotHashAtOrAfter(revealSlot);
+    const randomValue = Buffer.from(
+      keccak_256(Buffer.concat([secret, slotHash, user.publicKey.toBuffer()]))
+    ).readUInt32LE(0);
+    const state = await generationState(user.publicKey);
+    const expected =
+      state.rangeMin + nthFree(state.bitmap, state.capacity, randomValue % (state.capacity - state.issued));
+
+    const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+    await program.methods
+      .revealGeneration(Array.from(secret))
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        numberPool: numberPoolPda,
+        pending: pendingPda(user.publicKey),
+        userAccount: userAccountPda(user.publicKey),
+        user: user.publicKey,
+        treasury: treasuryPda,
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: numberRecordPda(expected), isWritable: true, isSigner: false },
+      ])
+      .signers([user])
+      .rpc();
+
+    const userAccount = await program.account.userAccount.fetch(userAccountPda(user.publicKey));
+    expect(userAccount.numbers).to.deep.equal([expected]);
+
+    // 暂存的费用转入财库，请求账户被关闭
+    const treasuryAfter = await provider.connection.getBalance(treasuryPda);
+    expect(treasuryAfter - treasuryBefore).to.equal(100000);
+    expect(await provider.connection.getAccountInfo(pendingPda(user.publicKey))).to.be.null;
+  });
+
+  // 测试：揭示错误的秘密值应失败，未过期的请求不能退款，过期后扣除部分费用退款
+  it("错误的秘密值无法揭示，过期前无法退款，过期后退还扣除部分费用之后的余额", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(user);
+
+    const secret = anchor.web3.Keypair.generate().publicKey.toBuffer();
+    await program.methods
+      .commitGeneration(Array.from(keccak_256(secret)), null)
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        pending: pendingPda(user.publicKey),
+        user: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    const pending = await program.account.pendingGeneration.fetch(pendingPda(user.publicKey));
+    await waitForSlot(pending.commitSlot.toNumber() + REVEAL_DELAY_SLOTS);
+
+    try {
+      await program.methods
+        .revealGeneration(Array.from(Buffer.alloc(32, 7)))
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          numberPool: numberPoolPda,
+          pending: pendingPda(user.publicKey),
+          userAccount: userAccountPda(user.publicKey),
+          user: user.publicKey,
+          treasury: treasuryPda,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidReveal");
+    }
+
+    const refund = () =>
+      program.methods
+        .refundGeneration()
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          pending: pendingPda(user.publicKey),
+          treasury: treasuryPda,
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+    try {
+      await refund();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("CommitmentNotExpired");
+    }
+
+    // 过期后一半费用转入财库，其余费用和租金退还给用户；没有进行中的轮次，不计入奖池
+    await waitForSlot(pending.commitSlot.toNumber() + COMMITMENT_EXPIRY_SLOTS);
+    const pendingLamports = await provider.connection.getBalance(pendingPda(user.publicKey));
+    const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+    const userBefore = await provider.connection.getBalance(user.publicKey);
+    const potBefore = (await program.account.pool.fetch(poolPda)).roundPot.toNumber();
+    const refundSignature = await refund();
+
+    expect(await provider.connection.getAccountInfo(pendingPda(user.publicKey))).to.be.null;
+    expect(await provider.connection.getBalance(treasuryPda)).to.equal(treasuryBefore + 50000);
+    expect((await program.account.pool.fetch(poolPda)).roundPot.toNumber()).to.equal(potBefore);
+    // 交易手续费由provider钱包支付
+    expect(await provider.connection.getBalance(user.publicKey)).to.equal(userBefore + pendingLamports - 50000);
+    const refunded = (await parseEvents(refundSignature)).find((event) => event.name === "generationRefunded");
+    expect(refunded.data.refunded.toNumber()).to.equal(50000);
+    expect(refunded.data.forfeited.toNumber()).to.equal(50000);
+  });
+
+  // 预言机模式：模拟预言机程序和固定的预言机授权密钥
+  const mockOracle = anchor.workspace.MockOracle as Program<MockOracle>;
+  const oracleAuthority = anchor.web3.Keypair.fromSecretKey(
+    Uint8Array.from(oracleAuthoritySecret)
+  );
+
+  // 计算预言机随机数请求账户的PDA地址
+  const vrfRequestPda = (user: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vrf_request"), poolPda.toBuffer(), user.toBuffer()],
+      program.programId
+    )[0];
+
+  // 计算模拟预言机一侧请求账户的PDA地址（以请求方地址为种子）
+  const oracleRequestPda = (requester: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("request"), requester.toBuffer()],
+      mockOracle.programId
+    )[0];
+
+  // 预言机回填超时的slot数，与程序中的VrfRequest::TIMEOUT_SLOTS一致
+  const VRF_TIMEOUT_SLOTS = 150;
+
+  // 发起预言机随机数请求
+  const requestVrf = (user: anchor.web3.Keypair) =>
+    program.methods
+      .requestVrfGeneration(null)
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        vrfRequest: vrfRequestPda(user.publicKey),
+        oracleRequest: oracleRequestPda(vrfRequestPda(user.publicKey)),
+        oracleProgram: mockOracle.programId,
+        user: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+  // 测试：管理员设置预言机
+  it("管理员设置预言机", async () => {
+    const signature = await program.methods
+      .setOracle(mockOracle.programId, oracleAuthority.publicKey)
+      .accounts({
+        config: configPda,
+        authority: authority,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const config = await program.account.luckSnakeConfig.fetch(configPda);
+    expect(config.oracleProgram.toString()).to.equal(mockOracle.programId.toString());
+    expect(config.oracleAuthority.toString()).to.equal(oracleAuthority.publicKey.toString());
+    const updated = (await parseEvents(signature)).find((event) => event.name === "oracleUpdated");
+    expect(updated.data.newOracleProgram.toString()).to.equal(mockOracle.programId.toString());
+    expect(updated.data.newOracleAuthority.toString()).to.equal(oracleAuthority.publicKey.toString());
+
+    // 预言机授权地址需要支付回填交易的手续费
+    await provider.connection.requestAirdrop(oracleAuthority.publicKey, LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+  });
+
+  // 测试：预言机模式完整流程：请求、回填、领取
+  it("预言机模式请求、回填并领取数字", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(user);
+
+    await requestVrf(user);
+
+    // 模拟预言机记录了由本程序请求PDA发起的请求
+    const oracleRequest = await mockOracle.account.randomnessRequest.fetch(
+      oracleRequestPda(vrfRequestPda(user.publicKey))
+    );
+    expect(oracleRequest.requester.toString()).to.equal(vrfRequestPda(user.publicKey).toString());
+    expect(oracleRequest.callbackProgram.toString()).to.equal(program.programId.toString());
+
+    // 固定授权密钥通过模拟预言机回填随机数，模拟预言机再回调本程序
+    const randomness = Array.from(anchor.web3.Keypair.generate().publicKey.toBuffer());
+    await mockOracle.methods
+      .fulfillRandomness(randomness)
+      .accounts({
+        authority: oracleAuthority.publicKey,
+        randomnessRequest: oracleRequestPda(vrfRequestPda(user.publicKey)),
+        callbackProgram: program.programId,
+      })
+      .remainingAccounts([
+        { pubkey: configPda, isWritable: false, isSigner: false },
+        { pubkey: vrfRequestPda(user.publicKey), isWritable: true, isSigner: false },
+      ])
+      .signers([oracleAuthority])
+      .rpc();
+
+    const vrfRequest = await program.account.vrfRequest.fetch(vrfRequestPda(user.publicKey));
+    expect(vrfRequest.fulfilled).to.be.true;
+    expect(vrfRequest.randomness).to.deep.equal(randomness);
+
+    // 随机数回填后即可算出被选中的数字
+    const randomValue = Buffer.from(
+      keccak_256(Buffer.concat([Buffer.from(randomness), user.publicKey.toBuffer()]))
+    ).readUInt32LE(0);
+    const state = await generationState(user.publicKey);
+    const expected =
+      state.rangeMin + nthFree(state.bitmap, state.capacity, randomValue % (state.capacity - state.issued));
+
+    await program.methods
+      .completeVrfGeneration()
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        numberPool: numberPoolPda,
+        vrfRequest: vrfRequestPda(user.publicKey),
+        userAccount: userAccountPda(user.publicKey),
+        user: user.publicKey,
+        treasury: treasuryPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: numberRecordPda(expected), isWritable: true, isSigner: false },
+      ])
+      .signers([user])
+      .rpc();
+
+    const userAccount = await program.account.userAccount.fetch(userAccountPda(user.publicKey));
+    expect(userAccount.numbers).to.deep.equal([expected]);
+    expect(await provider.connection.getAccountInfo(vrfRequestPda(user.publicKey))).to.be.null;
+  });
+
+  // 测试：非预言机授权地址无法回填随机数
+  it("非预言机授权地址无法回填随机数", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    await requestVrf(user);
+
+    try {
+      await program.methods
+        .fulfillVrfGeneration(Array.from(Buffer.alloc(32, 1)))
+        .accounts({
+          oracleAuthority: user.publicKey,
+          config: configPda,
+          vrfRequest: vrfRequestPda(user.publicKey),
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // 未回填的请求在超时前不能取消
+    try {
+      await program.methods
+        .cancelVrfGeneration()
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          vrfRequest: vrfRequestPda(user.publicKey),
+          user: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("VrfRequestNotTimedOut");
+    }
+  });
+
+  // 测试：预言机超时未回填时可以取消并退款
+  it("预言机超时后取消请求并退款", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    const balanceBefore = await provider.connection.getBalance(user.publicKey);
+    await requestVrf(user);
+    const vrfRequest = await program.account.vrfRequest.fetch(vrfRequestPda(user.publicKey));
+
+    await waitForSlot(vrfRequest.requestSlot.toNumber() + VRF_TIMEOUT_SLOTS);
+
+    // 超时后预言机不能再回填
+    try {
+      await mockOracle.methods
+        .fulfillRandomness(Array.from(Buffer.alloc(32, 2)))
+        .accounts({
+          authority: oracleAuthority.publicKey,
+          randomnessRequest: oracleRequestPda(vrfRequestPda(user.publicKey)),
+          callbackProgram: program.programId,
+        })
+        .remainingAccounts([
+          { pubkey: configPda, isWritable: false, isSigner: false },
+          { pubkey: vrfRequestPda(user.publicKey), isWritable: true, isSigner: false },
+        ])
+        .signers([oracleAuthority])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("VrfRequestTimedOut");
+    }
+
+    const cancelSignature = await program.methods
+      .cancelVrfGeneration()
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        vrfRequest: vrfRequestPda(user.publicKey),
+        user: user.publicKey,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    const cancelled = (await parseEvents(cancelSignature)).find((event) => event.name === "vrfCancelled");
+    expect(cancelled.data.user.toString()).to.equal(user.publicKey.toString());
+
+    // 费用和请求账户租金全部退还，用户只损失交易手续费和预言机请求账户的租金
+    expect(await provider.connection.getAccountInfo(vrfRequestPda(user.publicKey))).to.be.null;
+    const balanceAfter = await provider.connection.getBalance(user.publicKey);
+    const oracleRequestRent = await provider.connection.getBalance(
+      oracleRequestPda(vrfRequestPda(user.publicKey))
+    );
+    expect(balanceBefore - balanceAfter).to.be.lte(oracleRequestRent + 20000);
+  });
+
+  // 测试：管理员修改生成价格
+  it("管理员修改生成价格", async () => {
+    const signature = await program.methods
+      .updatePool(new anchor.BN(200000), null, null)
+      .accounts({
+        pool: poolPda,
+        authority: authority,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    let pool = await program.account.pool.fetch(poolPda);
+    expect(pool.generationPrice.toNumber()).to.equal(200000);
+
+    // 事件中记录了修改前后的值
+    const events = await parseEvents(signature);
+    const updated = events.find((event) => event.name === "poolUpdated");
+    expect(updated.data.pool.toString()).to.equal(poolPda.toString());
+    expect(updated.data.oldGenerationPrice.toNumber()).to.equal(100000);
+    expect(updated.data.newGenerationPrice.toNumber()).to.equal(200000);
+
+    // 不提供参数时数字池保持不变
+    await program.methods
+      .updatePool(null, null, null)
+      .accounts({ pool: poolPda, authority: authority })
+      .rpc();
+    pool = await program.account.pool.fetch(poolPda);
+    expect(pool.generationPrice.toNumber()).to.equal(200000);
+
+    // 恢复原价格
+    await program.methods
+      .updatePool(new anchor.BN(100000), null, null)
+      .accounts({ pool: poolPda, authority: authority })
+      .rpc();
+
+    pool = await program.account.pool.fetch(poolPda);
+    expect(pool.generationPrice.toNumber()).to.equal(100000);
+    expect(pool.treasury.toString()).to.equal(treasuryPda.toString());
+  });
+
+  // 测试：超出范围的价格应被拒绝
+  it("拒绝超出范围的价格", async () => {
+    for (const price of [0, 10_000_000_001]) {
+      try {
+        await program.methods
+          .updatePool(new anchor.BN(price), null, null)
+          .accounts({ pool: poolPda, authority: authority })
+          .rpc();
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidGenerationPrice");
+      }
+    }
+
+  });
+
+  // 测试：无效的奖级表应被拒绝
+  it("拒绝无效的奖级表", async () => {
+    const invalidTiers: [{ matchDigits: number; shareBps: number }[], string][] = [
+      // 份额之和超过10,000基点
+      [[{ matchDigits: 3, shareBps: 6000 }, { matchDigits: 1, shareBps: 5000 }], "InvalidPrizeShare"],
+      // 匹配位数重复
+      [[{ matchDigits: 2, shareBps: 1000 }, { matchDigits: 2, shareBps: 1000 }], "InvalidPrizeTiers"],
+      // 匹配位数超出范围
+      [[{ matchDigits: 4, shareBps: 1000 }], "InvalidPrizeTiers"],
+      [[{ matchDigits: 0, shareBps: 1000 }], "InvalidPrizeTiers"],
+      // 份额为0的奖级
+      [[{ matchDigits: 3, shareBps: 5000 }, { matchDigits: 2, shareBps: 0 }], "InvalidPrizeShare"],
+    ];
+    for (const [tiers, expected] of invalidTiers) {
+      try {
+        await program.methods
+          .updatePool(null, tiers, null)
+          .accounts({ pool: poolPda, authority: authority })
+          .rpc();
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include(expected);
+      }
+    }
+
+    // 奖级表保持默认值：完全匹配获得奖池的50%
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.prizeTiers[0].matchDigits).to.equal(3);
+    expect(pool.prizeTiers[0].shareBps).to.equal(5000);
+    expect(pool.prizeTiers[1].matchDigits).to.equal(0);
+  });
+
+  // 测试：非数字池管理员修改数字池应失败
+  it("非管理员尝试修改数字池应失败", async () => {
+    const nonAuthority = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .updatePool(new anchor.BN(1000), null, null)
+        .accounts({
+          pool: poolPda,
+          authority: nonAuthority.publicKey,
+        })
+        .signers([nonAuthority])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // 数字池保持不变
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.generationPrice.toNumber()).to.equal(100000);
+  });
+
+  // 测试：两步转移管理员
+  it("两步转移管理员：提议、取消、接受", async () => {
+    const newAuthority = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+
+    // 提议新管理员后，当前管理员保持不变
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accounts({ config: configPda, authority: authority })
+      .rpc();
+    let config = await program.account.luckSnakeConfig.fetch(configPda);
+    expect(config.authority.toString()).to.equal(authority.toString());
+    expect(config.pendingAuthority.toString()).to.equal(newAuthority.publicKey.toString());
+
+    // 非待接受地址无法接受
+    try {
+      await program.methods
+        .acceptAuthority()
+        .accounts({ config: configPda, newAuthority: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // 取消后待接受地址被清除，新管理员无法再接受
+    await program.methods
+      .cancelAuthorityTransfer()
+      .accounts({ config: configPda, authority: authority })
+      .rpc();
+    config = await program.account.luckSnakeConfig.fetch(configPda);
+    expect(config.pendingAuthority.toString()).to.equal(PublicKey.default.toString());
+    try {
+      await program.methods
+        .acceptAuthority()
+        .accounts({ config: configPda, newAuthority: newAuthority.publicKey })
+        .signers([newAuthority])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NoPendingAuthority");
+    }
+
+    // 重新提议并由新管理员接受
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accounts({ config: configPda, authority: authority })
+      .rpc();
+    await program.methods
+      .acceptAuthority()
+      .accounts({ config: configPda, newAuthority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    config = await program.account.luckSnakeConfig.fetch(configPda);
+    expect(config.authority.toString()).to.equal(newAuthority.publicKey.toString());
+    expect(config.pendingAuthority.toString()).to.equal(PublicKey.default.toString());
+
+    // 原管理员失去权限
+    try {
+      await program.methods
+        .setOracle(mockOracle.programId, oracleAuthority.publicKey)
+        .accounts({ config: configPda, authority: authority })
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // 将管理员转移回测试钱包，供后续测试使用
+    await program.methods
+      .proposeAuthority(authority)
+      .accounts({ config: configPda, authority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptAuthority()
+      .accounts({ config: configPda, newAuthority: authority })
+      .rpc();
+    config = await program.account.luckSnakeConfig.fetch(configPda);
+    expect(config.authority.toString()).to.equal(authority.toString());
+  });
+
+  // 测试：迁移配置账户
+  it("迁移配置账户：已是最新版本时不做修改，非管理员无法迁移", async () => {
+    const before = await provider.connection.getAccountInfo(configPda);
+
+    const signature = await program.methods
+      .migrateConfig()
+      .accounts({
+        config: configPda,
+        authority: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const after = await provider.connection.getAccountInfo(configPda);
+    expect(after.data.length).to.equal(before.data.length);
+    expect(after.data.equals(before.data)).to.be.true;
+
+    // 已是最新版本时事件中迁移前后的大小相同
+    const migrated = (await parseEvents(signature)).find((event) => event.name === "configMigrated");
+    expect(migrated.data.authority.toString()).to.equal(authority.toString());
+    expect(migrated.data.oldLen).to.equal(before.data.length);
+    expect(migrated.data.newLen).to.equal(before.data.length);
+
+    const nonAuthority = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .migrateConfig()
+        .accounts({
+          config: configPda,
+          authority: nonAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([nonAuthority])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+  });
+
+  // 测试：暂停和恢复生成
+  it("守护者暂停生成，暂停期间拒绝生成但可以关闭账户，管理员恢复", async () => {
+    const guardian = anchor.web3.Keypair.generate();
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await initUser(user);
+
+    // 管理员设置守护者
+    await program.methods
+      .setGuardian(guardian.publicKey)
+      .accounts({ config: configPda, authority: authority })
+      .rpc();
+
+    // 其他人不能暂停
+    const stranger = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .pause()
+        .accounts({ config: configPda, signer: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // 暂停之前提交一个生成请求
+    const secret = anchor.web3.Keypair.generate().publicKey.toBuffer();
+    await program.methods
+      .commitGeneration(Array.from(keccak_256(secret)), null)
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        pending: pendingPda(user.publicKey),
+        user: user.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    // 守护者暂停生成
+    await program.methods
+      .pause()
+      .accounts({ config: configPda, signer: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+    let config = await program.account.luckSnakeConfig.fetch(configPda);
+    expect(config.paused).to.be.true;
+    expect(config.pausedSlot.toNumber()).to.be.gt(0);
+
+    // 暂停挡住了揭示，不需要等待过期即可全额退款
+    const pendingLamports = await provider.connection.getBalance(pendingPda(user.publicKey));
+    const userBefore = await provider.connection.getBalance(user.publicKey);
+    const refundSignature = await program.methods
+      .refundGeneration()
+      .accounts({
+        config: configPda,
+        pool: poolPda,
+        pending: pendingPda(user.publicKey),
+        treasury: treasuryPda,
+        user: user.publicKey,
+      })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    expect(await provider.connection.getBalance(user.publicKey)).to.equal(userBefore + pendingLamports);
+    const refunded = (await parseEvents(refundSignature)).find((event) => event.name === "generationRefunded");
+    expect(refunded.data.forfeited.toNumber()).to.equal(0);
+
+    // 暂停期间生成数字被拒绝
+    try {
+      await program.methods
+        .generateNumber(null)
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          numberPool: numberPoolPda,
+          userAccount: userAccountPda(user.publicKey),
+          user: user.publicKey,
+          treasury: treasuryPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await candidateRecords(user.publicKey))
+        .signers([user])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("GenerationPaused");
+    }
+
+    // 暂停期间仍可以关闭用户账户
+    await program.methods
+      .closeUserAccount()
+      .accounts({
+        userAccount: userAccountPda(user.publicKey),
+        user: user.publicKey,
+      })
+      .signers([user])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(userAccountPda(user.publicKey))).to.be.null;
+
+    // 守护者不能恢复生成
+    try {
+      await program.methods
+        .unpause()
+        .accounts({ config: configPda, authority: guardian.publicKey })
+        .signers([guardian])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // 管理员恢复生成并移除守护者
+    await program.methods
+      .unpause()
+      .accounts({ config: configPda, authority: authority })
+      .rpc();
+    await program.methods
+      .setGuardian(PublicKey.default)
+      .accounts({ config: configPda, authority: authority })
+      .rpc();
+    config = await program.account.luckSnakeConfig.fetch(configPda);
+    expect(config.paused).to.be.false;
+    expect(config.unpausedSlot.toNumber()).to.be.gt(config.pausedSlot.toNumber());
+    expect(config.guardian.toString()).to.equal(PublicKey.default.toString());
+  });
+
+  // 测试：多个数字池互相独立
+  it("第二个数字池拥有独立的管理员、价格、范围和数字记录", async () => {
+    const secondPoolPda = poolPdaFor(1);
+    const poolAuthority = anchor.web3.Keypair.generate();
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    // 只有全局管理员可以创建数字池，数字池管理员可以是其他地址
+    await program.methods
+      .createPool(new anchor.BN(1), poolAuthority.publicKey, new anchor.BN(50000), 100, 199)
+      .accounts(createPoolAccounts(secondPoolPda))
+      .rpc();
+
+    // 在第二个数字池中注册并生成数字
+    await initUser(user, secondPoolPda);
+    const treasuryBefore = await provider.connection.getBalance(poolChildPda("treasury", secondPoolPda));
+    await program.methods
+      .generateNumber(null)
+      .accounts({
+        config: configPda,
+        pool: secondPoolPda,
+        numberPool: poolChildPda("number_pool", secondPoolPda),
+        userAccount: userAccountPda(user.publicKey, secondPoolPda),
+        user: user.publicKey,
+        treasury: poolChildPda("treasury", secondPoolPda),
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(await candidateRecords(user.publicKey, 1, secondPoolPda))
+      .signers([user])
+      .rpc();
+
+    // 费用按第二个数字池的价格支付到它自己的财库
+    const treasuryAfter = await provider.connection.getBalance(poolChildPda("treasury", secondPoolPda));
+    expect(treasuryAfter - treasuryBefore).to.equal(50000);
+
+    // 数字落在第二个数字池的范围内，数字记录以该数字池地址派生
+    const userAccount = await program.account.userAccount.fetch(
+      userAccountPda(user.publicKey, secondPoolPda)
+    );
+    const number = userAccount.numbers[0];
+    expect(number).to.be.gte(100);
+    expect(number).to.be.lte(199);
+    const record = await program.account.numberRecord.fetch(numberRecordPda(number, secondPoolPda));
+    expect(record.pool.toString()).to.equal(secondPoolPda.toString());
+    expect(record.owner.toString()).to.equal(user.publicKey.toString());
+
+    // 两个数字池的计数器互不影响
+    const secondPool = await program.account.pool.fetch(secondPoolPda);
+    expect(secondPool.totalGeneratedNumbers).to.equal(1);
+    expect(secondPool.authority.toString()).to.equal(poolAuthority.publicKey.toString());
+
+    // 全局管理员不能修改其他人管理的数字池
+    try {
+      await program.methods
+        .updatePool(new anchor.BN(60000), null, null)
+        .accounts({ pool: secondPoolPda, authority: authority })
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+  });
+
+  // 计算轮次账户的PDA地址（种子为"round"、数字池地址和轮次编号的小端字节）
+  const roundPda = (pool: PublicKey, roundId: number): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("round"), pool.toBuffer(), new anchor.BN(roundId).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  // 读取链上当前时间
+  const chainTime = async () =>
+    provider.connection.getBlockTime(await provider.connection.getSlot());
+
+  // 请求开奖
+  const requestDraw = (pool: PublicKey, round: PublicKey) =>
+    program.methods
+      .requestDraw()
+      .accounts({ pool, round })
+      .rpc({ commitment: "confirmed" });
+
+  // 开奖
+  const drawRound = (pool: PublicKey, round: PublicKey) =>
+    program.methods
+      .draw()
+      .accounts({
+        pool,
+        round,
+        numberPool: poolChildPda("number_pool", pool),
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .rpc({ commitment: "confirmed" });
+
+  // 等待截止时间后请求开奖，等到目标slot的区块哈希产生后开奖，返回开奖交易的签名
+  const closeAndDraw = async (pool: PublicKey, round: PublicKey, closeTime: number) => {
+    while ((await chainTime()) < closeTime) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    await requestDraw(pool, round);
+    await waitForSlot((await program.account.round.fetch(round)).drawSlot.toNumber());
+    return drawRound(pool, round);
+  };
+
+  // 测试：轮次开奖和领取奖金
+  it("开启轮次，截止后开奖，中奖数字的所有者领取奖金", async () => {
+    // 只有一个数字的数字池，发放后开奖必定命中该数字
+    const luckyPoolPda = poolPdaFor(2);
+    const luckyTreasury = poolChildPda("treasury", luckyPoolPda);
+    const user = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    await program.methods
+      .createPool(new anchor.BN(2), authority, new anchor.BN(100000), 7, 7)
+      .accounts(createPoolAccounts(luckyPoolPda))
+      .rpc();
+    await initUser(user, luckyPoolPda);
+
+    // 截止时间必须晚于当前时间
+    const round = roundPda(luckyPoolPda, 0);
+    try {
+      await program.methods
+        .openRound(new anchor.BN((await chainTime()) - 1))
+        .accounts({ pool: luckyPoolPda, round, authority, systemProgram: SystemProgram.programId })
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidRoundCloseTime");
+    }
+
+    const closeTime = (await chainTime()) + 8;
+    await program.methods
+      .openRound(new anchor.BN(closeTime))
+      .accounts({ pool: luckyPoolPda, round, authority, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    // 轮次进行中收取的费用计入本轮奖池
+    await program.methods
+      .generateNumber(null)
+      .accounts({
+        config: configPda,
+        pool: luckyPoolPda,
+        numberPool: poolChildPda("number_pool", luckyPoolPda),
+        userAccount: userAccountPda(user.publicKey, luckyPoolPda),
+        user: user.publicKey,
+        treasury: luckyTreasury,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(await candidateRecords(user.publicKey, 1, luckyPoolPda))
+      .signers([user])
+      .rpc();
+    expect((await program.account.pool.fetch(luckyPoolPda)).roundPot.toNumber()).to.equal(100000);
+
+    // 截止之前不能请求开奖，请求开奖之前不能开奖
+    try {
+      await requestDraw(luckyPoolPda, round);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("RoundNotClosed");
+    }
+    try {
+      await drawRound(luckyPoolPda, round);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("DrawNotRequested");
+    }
+
+    // 截止之后请求开奖，记录开奖使用的目标slot，同一轮不能重新请求
+    while ((await chainTime()) < closeTime) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    const requestSignature = await requestDraw(luckyPoolPda, round);
+    const drawSlot = (await program.account.round.fetch(round)).drawSlot.toNumber();
+    const requested = (await parseEvents(requestSignature)).find((event) => event.name === "drawRequested");
+    expect(requested.data.drawSlot.toNumber()).to.equal(drawSlot);
+    try {
+      await requestDraw(luckyPoolPda, round);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("DrawAlreadyRequested");
+    }
+
+    // 目标slot的区块哈希产生之后开奖
+    await waitForSlot(drawSlot);
+    const signature = await drawRound(luckyPoolPda, round);
+
+    // 奖池为本轮收取的一笔费用，默认奖金份额为50%
+    const drawn = await program.account.round.fetch(round);
+    expect(drawn.drawn).to.be.true;
+    expect(drawn.winningNumber).to.equal(7);
+    expect(drawn.pot.toNumber()).to.equal(100000);
+    expect(drawn.tierWinners[0]).to.equal(1);
+    expect(drawn.tierPrizes[0].toNumber()).to.equal(50000);
+    const events = await parseEvents(signature);
+    const drawnEvent = events.find((event) => event.name === "roundDrawn");
+    expect(drawnEvent.data.winningNumber).to.equal(7);
+
+    let pool = await program.account.pool.fetch(luckyPoolPda);
+    expect(pool.reservedPrizes.toNumber()).to.equal(50000);
+    expect(pool.roundPot.toNumber()).to.equal(0);
+
+    const claim = (winner: anchor.web3.Keypair) =>
+      program.methods
+        .claimPrize(7)
+        .accounts({
+          pool: luckyPoolPda,
+          round,
+          winnerAccount: null,
+          numberRecord: numberRecordPda(7, luckyPoolPda),
+          holderTokenAccount: null,
+          treasury: luckyTreasury,
+          winner: winner.publicKey,
+        })
+        .signers([winner])
+        .rpc();
+
+    // 非所有者不能领取
+    try {
+      await claim(stranger);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // 所有者领取奖金，之后不能重复领取
+    const balanceBefore = await provider.connection.getBalance(user.publicKey);
+    await claim(user);
+    const balanceAfter = await provider.connection.getBalance(user.publicKey);
+    expect(balanceAfter - balanceBefore).to.equal(50000);
+    try {
+      await claim(user);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("PrizeAlreadyClaimed");
+    }
+
+    pool = await program.account.pool.fetch(luckyPoolPda);
+    expect(pool.reservedPrizes.toNumber()).to.equal(0);
+  });
+
+  // 测试：按奖级领取奖金
+  it("按末尾数字匹配位数划分奖级，每个命中数字分别领奖", async () => {
+    // 0-19共20个数字全部发放，中奖数字w完全匹配，与w末位相同的另一个数字命中末位奖级
+    const tierPoolPda = poolPdaFor(3);
+    const tierTreasury = poolChildPda("treasury", tierPoolPda);
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 5 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    await program.methods
+      .createPool(new anchor.BN(3), authority, new anchor.BN(100000), 0, 19)
+      .accounts(createPoolAccounts(tierPoolPda))
+      .rpc();
+    await program.methods
+      .updatePool(null, [
+        { matchDigits: 3, shareBps: 5000 },
+        { matchDigits: 2, shareBps: 1000 },
+        { matchDigits: 1, shareBps: 2000 },
+      ], null)
+      .accounts({ pool: tierPoolPda, authority: authority })
+      .rpc();
+
+    await initUser(user, tierPoolPda);
+
+    // 先开启轮次，20笔费用都计入本轮奖池
+    const round = roundPda(tierPoolPda, 0);
+    const closeTime = (await chainTime()) + 30;
+    await program.methods
+      .openRound(new anchor.BN(closeTime))
+      .accounts({ pool: tierPoolPda, round, authority, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    // 开启轮次时保存奖级表，之后修改数字池的奖级不影响本轮开奖
+    await program.methods
+      .updatePool(null, [{ matchDigits: 3, shareBps: 1 }], null)
+      .accounts({ pool: tierPoolPda, authority: authority })
+      .rpc();
+    expect((await program.account.round.fetch(round)).prizeTiers[2].shareBps).to.equal(2000);
+    for (let i = 0; i < 20; i++) {
+      await program.methods
+        .generateNumber(null)
+        .accounts({
+          config: configPda,
+          pool: tierPoolPda,
+          numberPool: poolChildPda("number_pool", tierPoolPda),
+          userAccount: userAccountPda(user.publicKey, tierPoolPda),
+          user: user.publicKey,
+          treasury: tierTreasury,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(await candidateRecords(user.publicKey, 1, tierPoolPda))
+        .signers([user])
+        .rpc();
+    }
+
+    await closeAndDraw(tierPoolPda, round, closeTime);
+
+    // 奖池为20笔费用；0-19范围内末两位相同即完全相同，末两位奖级没有额外的命中数字
+    // 随机发放的数字不一定有数字记录，领奖时以用户账户证明所有权
+    const drawn = await program.account.round.fetch(round);
+    const winning = drawn.winningNumber;
+    const partner = winning < 10 ? winning + 10 : winning - 10;
+    const pot = 20 * 100000;
+    expect(drawn.pot.toNumber()).to.equal(pot);
+    expect(drawn.tierWinners).to.deep.equal([1, 0, 1]);
+    expect(drawn.tierPrizes[0].toNumber()).to.equal(pot / 2);
+    expect(drawn.tierPrizes[1].toNumber()).to.equal(0);
+    expect(drawn.tierPrizes[2].toNumber()).to.equal(pot / 5);
+
+    // 没有命中数字的末两位奖级份额滚入下一轮
+    let pool = await program.account.pool.fetch(tierPoolPda);
+    expect(pool.roundPot.toNumber()).to.equal(pot / 10);
+    expect(pool.reservedPrizes.toNumber()).to.equal(pot / 2 + pot / 5);
+
+    const claim = (number: number) =>
+      program.methods
+        .claimPrize(number)
+        .accounts({
+          pool: tierPoolPda,
+          round,
+          winnerAccount: userAccountPda(user.publicKey, tierPoolPda),
+          numberRecord: null,
+          holderTokenAccount: null,
+          treasury: tierTreasury,
+          winner: user.publicKey,
+        })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+    // 完全匹配和末位匹配的数字分别领奖
+    const exactEvents = await parseEvents(await claim(winning));
+    const exactClaim = exactEvents.find((event) => event.name === "prizeClaimed");
+    expect(exactClaim.data.matchDigits).to.equal(3);
+    expect(exactClaim.data.amount.toNumber()).to.equal(pot / 2);
+    const partnerEvents = await parseEvents(await claim(partner));
+    const partnerClaim = partnerEvents.find((event) => event.name === "prizeClaimed");
+    expect(partnerClaim.data.matchDigits).to.equal(1);
+    expect(partnerClaim.data.amount.toNumber()).to.equal(pot / 5);
+
+    // 未命中任何奖级的数字不能领奖
+    const loser = [...Array(20).keys()].find((n) => n % 10 !== winning % 10);
+    try {
+      await claim(loser);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NoPrize");
+    }
+
+    pool = await program.account.pool.fetch(tierPoolPda);
+    expect(pool.reservedPrizes.toNumber()).to.equal(0);
+  });
+
+  // 测试：开奖之后发放的数字不能领取该轮奖金
+  it("开奖之后买入的命中数字不能领奖", async () => {
+    // 0-19范围内开奖前买入0-9，每个末位各一个数字；只设末位奖级，中奖数字的末位必有一个命中数字
+    const latePoolPda = poolPdaFor(9);
+    const lateTreasury = poolChildPda("treasury", latePoolPda);
+    const user = anchor.web3.Keypair.generate();
+    const lateBuyer = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await provider.connection.requestAirdrop(lateBuyer.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    await program.methods
+      .createPool(new anchor.BN(9), authority, new anchor.BN(100000), 0, 19)
+      .accounts(createPoolAccounts(latePoolPda))
+      .rpc();
+    await program.methods
+      .updatePool(null, [{ matchDigits: 1, shareBps: 5000 }], null)
+      .accounts({ pool: latePoolPda, authority: authority })
+      .rpc();
+
+    const claimSpecific = (buyer: anchor.web3.Keypair, number: number) =>
+      program.methods
+        .claimSpecificNumber(number, null)
+        .accounts({
+          config: configPda,
+          pool: latePoolPda,
+          numberPool: poolChildPda("number_pool", latePoolPda),
+          numberRecord: numberRecordPda(number, latePoolPda),
+          userAccount: userAccountPda(buyer.publicKey, latePoolPda),
+          user: buyer.publicKey,
+          treasury: lateTreasury,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+    await initUser(user, latePoolPda);
+    await initUser(lateBuyer, latePoolPda);
+    const round = roundPda(latePoolPda, 0);
+    const closeTime = (await chainTime()) + 15;
+    await program.methods
+      .openRound(new anchor.BN(closeTime))
+      .accounts({ pool: latePoolPda, round, authority, systemProgram: SystemProgram.programId })
+      .rpc();
+    for (let number = 0; number < 10; number++) {
+      await claimSpecific(user, number);
+    }
+    await closeAndDraw(latePoolPda, round, closeTime);
+
+    const drawn = await program.account.round.fetch(round);
+    const earlyWinner = drawn.winningNumber % 10;
+    const lateNumber = earlyWinner + 10;
+    expect(drawn.tierWinners[0]).to.equal(1);
+    expect(drawn.tierPrizes[0].toNumber()).to.equal(500000);
+
+    // 开奖后数字池恢复发放，末位与中奖数字相同的数字仍可买入，但不在开奖时的快照中
+    await claimSpecific(lateBuyer, lateNumber);
+    const claim = (winner: anchor.web3.Keypair, number: number) =>
+      program.methods
+        .claimPrize(number)
+        .accounts({
+          pool: latePoolPda,
+          round,
+          winnerAccount: userAccountPda(winner.publicKey, latePoolPda),
+          numberRecord: null,
+          holderTokenAccount: null,
+          treasury: lateTreasury,
+          winner: winner.publicKey,
+        })
+        .signers([winner])
+        .rpc();
+    try {
+      await claim(lateBuyer, lateNumber);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NumberNotEligible");
+    }
+
+    // 开奖前发放的命中数字照常领取，预留的奖金全部释放
+    const balanceBefore = await provider.connection.getBalance(user.publicKey);
+    await claim(user, earlyWinner);
+    expect(await provider.connection.getBalance(user.publicKey)).to.equal(balanceBefore + 500000);
+    const pool = await program.account.pool.fetch(latePoolPda);
+    expect(pool.reservedPrizes.toNumber()).to.equal(0);
+  });
+
+  // 测试：以SPL代币支付
+  it("以SPL代币支付生成费用，管理员提取代币资金", async () => {
+    const tokenPoolPda = poolPdaFor(4);
+    const tokenTreasury = poolChildPda("treasury", tokenPoolPda);
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    await program.methods
+      .createPool(new anchor.BN(4), authority, new anchor.BN(50000), 0, 99)
+      .accounts(createPoolAccounts(tokenPoolPda))
+      .rpc();
+    await initUser(user, tokenPoolPda);
+
+    // 6位小数的代币，价格为2.5个代币
+    const mint = await createTestMint(6);
+    const tokenPrice = 2_500_000;
+    const userTokenAccount = await createTokenAccount(mint, user.publicKey);
+    await mintTokens(mint, userTokenAccount, 10 * tokenPrice);
+    const [treasuryTokenAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury_token"), tokenPoolPda.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+
+    // 代币价格不能为0
+    const setPaymentMint = (price: number) =>
+      program.methods
+        .setPaymentMint(new anchor.BN(price))
+        .accounts({
+          pool: tokenPoolPda,
+          paymentMint: mint,
+          treasuryTokenAccount,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+    try {
+      await setPaymentMint(0);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidTokenPrice");
+    }
+
+    const setSignature = await setPaymentMint(tokenPrice);
+    let pool = await program.account.pool.fetch(tokenPoolPda);
+    expect(pool.paymentMint.toString()).to.equal(mint.toString());
+    expect(pool.tokenPrice.toNumber()).to.equal(tokenPrice);
+    const updated = (await parseEvents(setSignature)).find((event) => event.name === "paymentMintUpdated");
+    expect(updated.data.oldPaymentMint.toString()).to.equal(PublicKey.default.toString());
+    expect(updated.data.newPaymentMint.toString()).to.equal(mint.toString());
+
+    const generateAccounts = {
+      config: configPda,
+      pool: tokenPoolPda,
+      numberPool: poolChildPda("number_pool", tokenPoolPda),
+      userAccount: userAccountPda(user.publicKey, tokenPoolPda),
+      user: user.publicKey,
+      treasury: tokenTreasury,
+      systemProgram: SystemProgram.programId,
+    };
+    const tokenAccounts = {
+      paymentMint: mint,
+      userTokenAccount,
+      treasuryTokenAccount,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    // 未提供代币支付账户时失败
+    try {
+      await program.methods
+        .generateNumber(null)
+        .accounts(generateAccounts)
+        .remainingAccounts(await candidateRecords(user.publicKey, 1, tokenPoolPda))
+        .signers([user])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("TokenPaymentAccountsMissing");
+    }
+
+    // 以代币支付，财库的lamports不变，费用不计入以lamports发放的奖池
+    const treasuryLamportsBefore = await provider.connection.getBalance(tokenTreasury);
+    const signature = await program.methods
+      .generateNumber(null)
+      .accounts({ ...generateAccounts, ...tokenAccounts })
+      .remainingAccounts(await candidateRecords(user.publicKey, 1, tokenPoolPda))
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    expect(await tokenBalance(userTokenAccount)).to.equal(9 * tokenPrice);
+    expect(await tokenBalance(treasuryTokenAccount)).to.equal(tokenPrice);
+    expect(await provider.connection.getBalance(tokenTreasury)).to.equal(treasuryLamportsBefore);
+    pool = await program.account.pool.fetch(tokenPoolPda);
+    expect(pool.totalGeneratedNumbers).to.equal(1);
+    expect(pool.roundPot.toNumber()).to.equal(0);
+    const generated = (await parseEvents(signature)).find((event) => event.name === "numberGenerated");
+    expect(generated.data.pricePaid.toNumber()).to.equal(tokenPrice);
+
+    // 指定数字按代币价格乘以价格倍数（默认1倍）收取
+    const taken = (await program.account.userAccount.fetch(userAccountPda(user.publicKey, tokenPoolPda))).numbers;
+    const specific = taken[0] === 0 ? 1 : 0;
+    await program.methods
+      .claimSpecificNumber(specific, null)
+      .accounts({
+        ...generateAccounts,
+        numberRecord: numberRecordPda(specific, tokenPoolPda),
+        ...tokenAccounts,
+      })
+      .signers([user])
+      .rpc();
+    expect(await tokenBalance(treasuryTokenAccount)).to.equal(2 * tokenPrice);
+
+    // 提交-揭示模式以lamports暂存费用，代币支付的数字池不支持
+    try {
+      await program.methods
+        .commitGeneration(Array.from(keccak_256(Buffer.alloc(32))), null)
+        .accounts({
+          config: configPda,
+          pool: tokenPoolPda,
+          pending: pendingPda(user.publicKey, tokenPoolPda),
+          user: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("TokenPaymentNotSupported");
+    }
+
+    // 管理员把代币提取到自己的代币账户
+    const recipientTokenAccount = await createTokenAccount(mint, authority);
+    const withdrawTokens = (amount: number) =>
+      program.methods
+        .withdrawTokenFunds(new anchor.BN(amount))
+        .accounts({
+          config: configPda,
+          pool: tokenPoolPda,
+          paymentMint: mint,
+          treasuryTokenAccount,
+          recipientTokenAccount,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+    try {
+      await withdrawTokens(2 * tokenPrice + 1);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientTreasuryFunds");
+    }
+    const withdrawSignature = await withdrawTokens(2 * tokenPrice);
+    expect(await tokenBalance(recipientTokenAccount)).to.equal(2 * tokenPrice);
+    expect(await tokenBalance(treasuryTokenAccount)).to.equal(0);
+    const withdrawn = (await parseEvents(withdrawSignature)).find((event) => event.name === "tokenFundsWithdrawn");
+    expect(withdrawn.data.mint.toString()).to.equal(mint.toString());
+    expect(withdrawn.data.amount.toNumber()).to.equal(2 * tokenPrice);
+    expect(withdrawn.data.treasuryBalance.toNumber()).to.equal(0);
+
+    // 清除支付代币后恢复以lamports支付
+    await program.methods
+      .clearPaymentMint()
+      .accounts({ pool: tokenPoolPda, authority })
+      .rpc();
+    pool = await program.account.pool.fetch(tokenPoolPda);
+    expect(pool.paymentMint.toString()).to.equal(PublicKey.default.toString());
+    await program.methods
+      .generateNumber(null)
+      .accounts(generateAccounts)
+      .remainingAccounts(await candidateRecords(user.publicKey, 1, tokenPoolPda))
+      .signers([user])
+      .rpc();
+    expect(await provider.connection.getBalance(tokenTreasury)).to.equal(treasuryLamportsBefore + 50000);
+    expect(await tokenBalance(userTokenAccount)).to.equal(8 * tokenPrice);
+  });
+
+  // 测试：Token-2022支付代币
+  it("Token-2022带转账手续费的支付代币由用户承担手续费，拒绝不支持的扩展", async () => {
+    const feePoolPda = poolPdaFor(5);
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    await program.methods
+      .createPool(new anchor.BN(5), authority, new anchor.BN(50000), 0, 99)
+      .accounts(createPoolAccounts(feePoolPda))
+      .rpc();
+    await initUser(user, feePoolPda);
+
+    const treasuryTokenPda = (mint: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury_token"), feePoolPda.toBuffer(), mint.toBuffer()],
+        program.programId
+      )[0];
+    const setPaymentMint = (mint: PublicKey, price: number) =>
+      program.methods
+        .setPaymentMint(new anchor.BN(price))
+        .accounts({
+          pool: feePoolPda,
+          paymentMint: mint,
+          treasuryTokenAccount: treasuryTokenPda(mint),
+          authority,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // 不可转让和带永久委托人的mint被拒绝
+    const nonTransferableMint = await createTestMint(6, TOKEN_2022_PROGRAM_ID, {
+      space: 170,
+      instructions: [Buffer.from([32])], // InitializeNonTransferableMint
+    });
+    try {
+      await setPaymentMint(nonTransferableMint, 1_000_000);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NonTransferableMint");
+    }
+    const permanentDelegateMint = await createTestMint(6, TOKEN_2022_PROGRAM_ID, {
+      space: 202,
+      instructions: [Buffer.concat([Buffer.from([35]), authority.toBuffer()])], // InitializePermanentDelegate
+    });
+    try {
+      await setPaymentMint(permanentDelegateMint, 1_000_000);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("PermanentDelegateMint");
+    }
+
+    // 转账手续费1%、上限1个代币的mint
+    const feeBps = 100;
+    const feeConfig = Buffer.alloc(14);
+    feeConfig.writeUInt8(26, 0); // TransferFeeExtension
+    feeConfig.writeUInt8(0, 1); // InitializeTransferFeeConfig，不设费率和提取权限
+    feeConfig.writeUInt16LE(feeBps, 4);
+    feeConfig.writeBigUInt64LE(BigInt(1_000_000), 6);
+    const mint = await createTestMint(6, TOKEN_2022_PROGRAM_ID, {
+      space: 278,
+      instructions: [feeConfig],
+    });
+    const tokenPrice = 1_000_000;
+    await setPaymentMint(mint, tokenPrice);
+
+    const userTokenAccount = await createTokenAccount(mint, user.publicKey, TOKEN_2022_PROGRAM_ID);
+    await mintTokens(mint, userTokenAccount, 10 * tokenPrice, TOKEN_2022_PROGRAM_ID);
+    await program.methods
+      .generateNumber(null)
+      .accounts({
+        config: configPda,
+        pool: feePoolPda,
+        numberPool: poolChildPda("number_pool", feePoolPda),
+        userAccount: userAccountPda(user.publicKey, feePoolPda),
+        user: user.publicKey,
+        treasury: poolChildPda("treasury", feePoolPda),
+        systemProgram: SystemProgram.programId,
+        paymentMint: mint,
+        userTokenAccount,
+        treasuryTokenAccount: treasuryTokenPda(mint),
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .remainingAccounts(await candidateRecords(user.publicKey, 1, feePoolPda))
+      .signers([user])
+      .rpc();
+
+    // 财库收到完整的价格，用户额外支付手续费：ceil(1,000,000 * 10,000 / 9,900) = 1,010,102
+    expect(await tokenBalance(treasuryTokenPda(mint))).to.equal(tokenPrice);
+    expect(10 * tokenPrice - (await tokenBalance(userTokenAccount))).to.equal(1_010_102);
+
+    // 管理员提取时接收方承担手续费
+    const recipientTokenAccount = await createTokenAccount(mint, authority, TOKEN_2022_PROGRAM_ID);
+    await program.methods
+      .withdrawTokenFunds(new anchor.BN(tokenPrice))
+      .accounts({
+        config: configPda,
+        pool: feePoolPda,
+        paymentMint: mint,
+        treasuryTokenAccount: treasuryTokenPda(mint),
+        recipientTokenAccount,
+        authority,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+    expect(await tokenBalance(treasuryTokenPda(mint))).to.equal(0);
+    expect(await tokenBalance(recipientTokenAccount)).to.equal(tokenPrice - tokenPrice * feeBps / 10000);
+  });
+
+  // 测试：价格曲线
+  it("按价格曲线随发放数量涨价，查询价格并在超过最高价格时拒绝生成", async () => {
+    const curvePoolPda = poolPdaFor(6);
+    const curveTreasury = poolChildPda("treasury", curvePoolPda);
+    const user = anchor.web3.Keypair.generate();
+    await provider.connection.requestAirdrop(user.publicKey, 2 * LAMPORTS_PER_SOL);
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    await program.methods
+      .createPool(new anchor.BN(6), authority, new anchor.BN(10000), 0, 99)
+      .accounts(createPoolAccounts(curvePoolPda))
+      .rpc();
+    await initUser(user, curvePoolPda);
+
+    const setPriceCurve = (curve: object, slope: anchor.BN) =>
+      program.methods
+        .setPriceCurve(curve as any, slope)
+        .accounts({ pool: curvePoolPda, authority })
+        .rpc({ commitment: "confirmed" });
+    const quote = async (count: number) =>
+      (await program.methods.quotePrice(count).accounts({ pool: curvePoolPda }).view()).toNumber();
+    const generateAccounts = {
+      config: configPda,
+      pool: curvePoolPda,
+      numberPool: poolChildPda("number_pool", curvePoolPda),
+      userAccount: userAccountPda(user.publicKey, curvePoolPda),
+      user: user.publicKey,
+      treasury: curveTreasury,
+      systemProgram: SystemProgram.programId,
+    };
+    const generate = async (maxPrice: number | null) =>
+      program.methods
+        .generateNumber(maxPrice === null ? null : new anchor.BN(maxPrice))
+        .accounts(generateAccounts)
+        .remainingAccounts(await candidateRecords(user.publicKey, 1, curvePoolPda))
+        .signers([user])
+        .rpc();
+
+    // 线性曲线：每发放一个数字涨价1,000 lamports
+    const signature = await setPriceCurve({ linear: {} }, new anchor.BN(1000));
+    const updated = (await parseEvents(signature)).find((event) => event.name === "priceCurveUpdated");
+    expect(updated.data.newPriceCurve).to.deep.equal({ linear: {} });
+    expect(updated.data.newPriceCurveSlope.toNumber()).to.equal(1000);
+    expect(await quote(1)).to.equal(10000);
+
+    let treasuryBefore = await provider.connection.getBalance(curveTreasury);
+    await generate(10000);
+    expect(await provider.connection.getBalance(curveTreasury)).to.equal(treasuryBefore + 10000);
+
+    // 下一个数字为11,000，接下来3个数字共11,000 + 12,000 + 13,000
+    expect(await quote(1)).to.equal(11000);
+    expect(await quote(3)).to.equal(36000);
+
+    // 价格超过最高价格时拒绝
+    try {
+      await generate(10999);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("PriceExceedsMax");
+    }
+    await generate(11000);
+
+    // 指数曲线：每发放一个数字涨价10%，已发放2个数字时为10,000 × 1.1^2
+    await setPriceCurve({ exponential: {} }, new anchor.BN(1000));
+    expect(await quote(1)).to.equal(12100);
+
+    // 批量生成按每个数字发放时的价格累加，最高价格限制总价
+    const batch = async (maxPrice: number) =>
+      program.methods
+        .generateNumbers(2, new anchor.BN(maxPrice))
+        .accounts(generateAccounts)
+        .remainingAccounts(await candidateRecords(user.publicKey, 2, curvePoolPda))
+        .signers([user])
+        .rpc();
+    try {
+      await batch(12100 + 13310 - 1);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("PriceExceedsMax");
+    }
+    treasuryBefore = await provider.connection.getBalance(curveTreasury);
+    await batch(12100 + 13310);
+    expect(await provider.connection.getBalance(curveTreasury)).to.equal(treasuryBefore + 12100 + 13310);
+
+    // 指数曲线涨幅超过上限、或范围内最后一个数字的价格溢出时拒绝
+    for (const [curve, slope] of [
+      [{ exponential: {} }, new anchor.BN(1001)],
+      [{ linear: {} }, new anchor.BN("18446744073709551615")],
+    ] as [object, anchor.BN][]) {
+      try {
+        await setPriceCurve(curve, slope);
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidPriceCurve");
+      }
+    }
+
+    // 恢复固定价格
+    await setPriceCurve({ flat: {} }, new anchor.BN(0));
+    expect(await quote(2)).to.equal(20000);
+  });
+
+  // 测试：推荐奖励
+  it("按推荐比例把生成费用分给推荐人，推荐人领取累计的奖励", async () => {
+    const referralPoolPda = poolPdaFor(7);
+    const referralTreasury = poolChildPda("treasury", referralPoolPda);
+    const user = anchor.web3.Keypair.generate();
+    const referrer = anchor.web3.Keypair.generate();
+    for (const wallet of [user, referrer]) {
+      await provider.connection.requestAirdrop(wallet.publicKey, 2 * LAMPORTS_PER_SOL);
+    }
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    await program.methods
+      .createPool(new anchor.BN(7), authority, new anchor.BN(100000), 0, 99)
+      .accounts(createPoolAccounts(referralPoolPda))
+      .rpc();
+    await initUser(user, referralPoolPda);
+
+    // 推荐奖励账户的PDA地址（种子为"referral"、数字池地址和推荐人地址）
+    const referralPdaFor = (wallet: PublicKey): PublicKey =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("referral"), referralPoolPda.toBuffer(), wallet.toBuffer()],
+        program.programId
+      )[0];
+    const referralPda = referralPdaFor(referrer.publicKey);
+    const initReferral = (wallet: anchor.web3.Keypair) =>
+      program.methods
+        .initReferral()
+        .accounts({
+          pool: referralPoolPda,
+          referralRewards: referralPdaFor(wallet.publicKey),
+          referrer: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([wallet])
+        .rpc({ commitment: "confirmed" });
+    const referralInitialized = (await parseEvents(await initReferral(referrer))).find(
+      (event) => event.name === "referralInitialized"
+    );
+    expect(referralInitialized.data.pool.toString()).to.equal(referralPoolPda.toString());
+    expect(referralInitialized.data.referrer.toString()).to.equal(referrer.publicKey.toString());
+    expect(referralInitialized.data.referralRewards.toString()).to.equal(
+      referralPdaFor(referrer.publicKey).toString()
+    );
+
+    // 推荐比例超过上限时拒绝，设为10%
+    try {
+      await program.methods
+        .setReferralBps(5001)
+        .accounts({ pool: referralPoolPda, authority })
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidReferralShare");
+    }
+    await program.methods
+      .setReferralBps(1000)
+      .accounts({ pool: referralPoolPda, authority })
+      .rpc();
+
+    const generate = async (referralRewards: PublicKey) =>
+      program.methods
+        .generateNumber(null)
+        .accounts({
+          config: configPda,
+          pool: referralPoolPda,
+          numberPool: poolChildPda("number_pool", referralPoolPda),
+          userAccount: userAccountPda(user.publicKey, referralPoolPda),
+          user: user.publicKey,
+          treasury: referralTreasury,
+          systemProgram: SystemProgram.programId,
+          referralRewards,
+        })
+        .remainingAccounts(await candidateRecords(user.publicKey, 1, referralPoolPda))
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+    // 费用的10%转入推荐奖励账户，其余转入财库
+    const treasuryBefore = await provider.connection.getBalance(referralTreasury);
+    const referralBefore = await provider.connection.getBalance(referralPda);
+    const signature = await generate(referralPda);
+    expect(await provider.connection.getBalance(referralTreasury)).to.equal(treasuryBefore + 90000);
+    expect(await provider.connection.getBalance(referralPda)).to.equal(referralBefore + 10000);
+
+    const accrued = (await parseEvents(signature)).find((event) => event.name === "referralRewardAccrued");
+    expect(accrued.data.referrer.toString()).to.equal(referrer.publicKey.toString());
+    expect(accrued.data.user.toString()).to.equal(user.publicKey.toString());
+    expect(accrued.data.amount.toNumber()).to.equal(10000);
+    expect(accrued.data.treasuryAmount.toNumber()).to.equal(90000);
+
+    let pool = await program.account.pool.fetch(referralPoolPda);
+    expect(pool.treasuryRevenue.toNumber()).to.equal(90000);
+    expect(pool.referralRevenue.toNumber()).to.equal(10000);
+    // 没有进行中的轮次，财库收到的费用不计入奖池
+    expect(pool.roundPot.toNumber()).to.equal(0);
+    let rewards = await program.account.referralRewards.fetch(referralPda);
+    expect(rewards.accrued.toNumber()).to.equal(10000);
+    expect(rewards.totalEarned.toNumber()).to.equal(10000);
+
+    // 用户不能推荐自己
+    await initReferral(user);
+    try {
+      await generate(referralPdaFor(user.publicKey));
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("SelfReferral");
+    }
+
+    // 推荐人领取全部累计奖励，账户租金保留
+    const claim = () =>
+      program.methods
+        .claimReferralRewards()
+        .accounts({ referralRewards: referralPda, referrer: referrer.publicKey })
+        .signers([referrer])
+        .rpc({ commitment: "confirmed" });
+    const referrerBefore = await provider.connection.getBalance(referrer.publicKey);
+    const claimSignature = await claim();
+    expect(await provider.connection.getBalance(referrer.publicKey)).to.equal(referrerBefore + 10000);
+    expect(await provider.connection.getBalance(referralPda)).to.equal(referralBefore);
+    const claimed = (await parseEvents(claimSignature)).find((event) => event.name === "referralRewardsClaimed");
+    expect(claimed.data.amount.toNumber()).to.equal(10000);
+
+    rewards = await program.account.referralRewards.fetch(referralPda);
+    expect(rewards.accrued.toNumber()).to.equal(0);
+    expect(rewards.totalEarned.toNumber()).to.equal(10000);
+
+    // 没有可领取的奖励时拒绝
+    try {
+      await claim();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NoReferralRewards");
+    }
+
+    // 不提供推荐奖励账户时全部费用计入财库收入
+    await generate(null);
+    pool = await program.account.pool.fetch(referralPoolPda);
+    expect(pool.treasuryRevenue.toNumber() + pool.referralRevenue.toNumber()).to.equal(200000);
+    expect(pool.treasuryRevenue.toNumber()).to.equal(190000);
+  });
+
+  // 测试：提取资金
+  it("提取指定金额并写入提取日志", async () => {
+    // 财库PDA中已有前面测试支付的费用
+    const recipient = anchor.web3.Keypair.generate();
+    const treasuryInfo = await provider.connection.getAccountInfo(treasuryPda);
+    const rentExemptMinimum = await provider.connection.getMinimumBalanceForRentExemption(
+      treasuryInfo.data.length
+    );
+
+    const withdraw = (amount: number) =>
+      program.methods
+        .withdrawFunds(new anchor.BN(amount))
+        .accounts({
+          config: configPda,                       // 配置账户，检查是否设置了分账表
+          pool: poolPda,                          // 数字池账户
+          authority: authority,                    // 数字池管理员账户
+          treasury: treasuryPda,                   // 财库PDA，无需签名
+          withdrawalLog: withdrawalLogPda,         // 提取日志PDA
+          recipient: recipient.publicKey,         // 接收方账户
+        })
+        .rpc({ commitment: "confirmed" });
+
+    // 数字池从未开启轮次，费用不计入奖池，不需要开奖就可以提取
+    const pool = await program.account.pool.fetch(poolPda);
+    expect(pool.roundPot.toNumber()).to.equal(0);
+    const treasuryBalanceBefore = await provider.connection.getBalance(treasuryPda);
+    const locked = pool.reservedPrizes.toNumber() + pool.roundPot.toNumber();
+    const withdrawable = treasuryBalanceBefore - rentExemptMinimum - locked;
+    expect(withdrawable).to.be.gt(100000);
+
+    // 提取部分资金
+    await withdraw(100000);
+    expect(await provider.connection.getBalance(recipient.publicKey)).to.equal(100000);
+
+    // 超过可提取金额（会动用预留的奖金、奖池或免租金最低余额）应失败
+    try {
+      await withdraw(withdrawable);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientTreasuryFunds");
+    }
+
+    // 提取金额为0应失败
+    try {
+      await withdraw(0);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidWithdrawalAmount");
+    }
+
+    // 提取剩余的全部可提取资金，财库保留免租金最低余额、预留的奖金和奖池
+    const signature = await withdraw(withdrawable - 100000);
+    expect(await provider.connection.getBalance(recipient.publicKey)).to.equal(withdrawable);
+    expect(await provider.connection.getBalance(treasuryPda)).to.equal(rentExemptMinimum + locked);
+
+    // 提取事件中记录了金额、剩余余额和累计提取次数
+    const events = await parseEvents(signature);
+    const withdrawn = events.find((event) => event.name === "fundsWithdrawn");
+    expect(withdrawn.data.pool.toString()).to.equal(poolPda.toString());
+    expect(withdrawn.data.amount.toNumber()).to.equal(withdrawable - 100000);
+    expect(withdrawn.data.treasuryBalance.toNumber()).to.equal(rentExemptMinimum + locked);
+    expect(withdrawn.data.totalWithdrawals.toNumber()).to.equal(2);
+
+    // 提取日志按顺序记录了两次成功的提取
+    const log = await program.account.withdrawalLog.fetch(withdrawalLogPda);
+    expect(log.totalWithdrawals.toNumber()).to.equal(2);
+    expect(log.records[0].amount.toNumber()).to.equal(100000);
+    expect(log.records[1].amount.toNumber()).to.equal(withdrawable - 100000);
+    expect(log.records[1].recipient.toString()).to.equal(recipient.publicKey.toString());
+    expect(log.records[1].timestamp.toNumber()).to.be.gt(0);
+
+    // 没有可提取资金时再次提取应失败
+    try {
+      await withdraw(1);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NoFundsToWithdraw");
+    }
+  });
+
+  // 测试：非管理员尝试提取资金应失败
+  it("非管理员尝试提取资金应失败", async () => {
+    // 创建非管理员账户
+    const nonAuthority = anchor.web3.Keypair.generate();
+    const recipient = anchor.web3.Keypair.generate();
+    
+    try {
+      // 尝试以非管理员身份提取资金
+      await program.methods
+        .withdrawFunds(new anchor.BN(1))
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          authority: nonAuthority.publicKey,    // 使用非管理员账户
+          treasury: treasuryPda,
+          withdrawalLog: withdrawalLogPda,
+          recipient: recipient.publicKey,
+        })
+        .signers([nonAuthority])
+        .rpc();
+      
+      // 如果没有抛出错误，测试失败
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      // 验证错误信息包含约束失败（can use "constraint" or check for specific error codes）
+      expect(error.toString()).to.include("ConstraintHasOne");
+    }
+  });
+
+  // 测试：固定接收方
+  it("程序管理员可以设置和轮换提取资金的固定接收方", async () => {
+    const nonAuthority = anchor.web3.Keypair.generate();
+    const recipientA = anchor.web3.Keypair.generate();
+    const recipientB = anchor.web3.Keypair.generate();
+    const setRecipient = (recipient: PublicKey, signer?: anchor.web3.Keypair) => {
+      const builder = program.methods
+        .setWithdrawalRecipient(recipient)
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          authority: signer ? signer.publicKey : authority,
+        });
+      return (signer ? builder.signers([signer]) : builder).rpc({ commitment: "confirmed" });
+    };
+    const withdrawTo = (recipient: PublicKey) =>
+      program.methods
+        .withdrawFunds(new anchor.BN(1))
+        .accounts({
+          config: configPda,
+          pool: poolPda,
+          authority,
+          treasury: treasuryPda,
+          withdrawalLog: withdrawalLogPda,
+          recipient,
+        })
+        .rpc();
+
+    // 非程序管理员不能设置
+    try {
+      await setRecipient(recipientA.publicKey, nonAuthority);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // 财库不能作为接收方
+    try {
+      await setRecipient(treasuryPda);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidWithdrawalRecipient");
+    }
+
+    // 设置接收方A，事件记录了修改前后的接收方
+    let signature = await setRecipient(recipientA.publicKey);
+    let updated = (await parseEvents(signature)).find((event) => event.name === "withdrawalRecipientUpdated");
+    expect(updated.data.pool.toString()).to.equal(poolPda.toString());
+    expect(updated.data.oldRecipient.toString()).to.equal(PublicKey.default.toString());
+    expect(updated.data.newRecipient.toString()).to.equal(recipientA.publicKey.toString());
+    let pool = await program.account.pool.fetch(poolPda);
+    expect(pool.withdrawalRecipient.toString()).to.equal(recipientA.publicKey.toString());
+
+    // 不能提取到其他地址；提取到接收方A时通过接收方检查，财库已没有可提取资金
+    try {
+      await withdrawTo(recipientB.publicKey);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidWithdrawalRecipient");
+    }
+    try {
+      await withdrawTo(recipientA.publicKey);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NoFundsToWithdraw");
+    }
+
+    // 轮换到接收方B后，接收方A不能再接收
+    signature = await setRecipient(recipientB.publicKey);
+    updated = (await parseEvents(signature)).find((event) => event.name === "withdrawalRecipientUpdated");
+    expect(updated.data.oldRecipient.toString()).to.equal(recipientA.publicKey.toString());
+    expect(updated.data.newRecipient.toString()).to.equal(recipientB.publicKey.toString());
+    try {
+      await withdrawTo(recipientA.publicKey);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidWithdrawalRecipient");
+    }
+
+    // 设置为默认值取消限制，不影响后续测试
+    signature = await setRecipient(PublicKey.default);
+    updated = (await parseEvents(signature)).find((event) => event.name === "withdrawalRecipientUpdated");
+    expect(updated.data.oldRecipient.toString()).to.equal(recipientB.publicKey.toString());
+    pool = await program.account.pool.fetch(poolPda);
+    expect(pool.withdrawalRecipient.toString()).to.equal(PublicKey.default.toString());
+  });
+
+  // 测试：分账
+  it("按分账表把财库资金分给多个接收方，任何人都可以调用分账", async () => {
+    const splitPoolPda = poolPdaFor(8);
+    const splitTreasury = poolChildPda("treasury", splitPoolPda);
+    const user = anchor.web3.Keypair.generate();
+    const partnerA = anchor.web3.Keypair.generate();
+    const partnerB = anchor.web3.Keypair.generate();
+    // 接收方预先持有免租金余额，分账指令不需要任何签名
+    for (const wallet of [user, partnerA, partnerB]) {
+      await provider.connection.requestAirdrop(wallet.publicKey, LAMPORTS_PER_SOL);
+    }
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+
+    await program.methods
+      .createPool(new anchor.BN(8), authority, new anchor.BN(100000), 0, 99)
+      .accounts(createPoolAccounts(splitPoolPda))
+      .rpc();
+    await initUser(user, splitPoolPda);
+    await program.methods
+      .generateNumbers(3, null)
+      .accounts({
+        config: configPda,
+        pool: splitPoolPda,
+        numberPool: poolChildPda("number_pool", splitPoolPda),
+        userAccount: userAccountPda(user.publicKey, splitPoolPda),
+        user: user.publicKey,
+        treasury: splitTreasury,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(await candidateRecords(user.publicKey, 3, splitPoolPda))
+      .signers([user])
+      .rpc();
+
+    const setFeeSplit = (recipients: { recipient: PublicKey; shareBps: number }[]) =>
+      program.methods
+        .setFeeSplit(recipients)
+        .accounts({ config: configPda, authority })
+        .rpc({ commitment: "confirmed" });
+    const distribute = (recipients: PublicKey[]) =>
+      program.methods
+        .distribute()
+        .accounts({ config: configPda, pool: splitPoolPda, treasury: splitTreasury })
+        .remainingAccounts(recipients.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+        .rpc({ commitment: "confirmed" });
+
+    // 未设置分账表时不能分账
+    try {
+      await distribute([]);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("FeeSplitNotSet");
+    }
+
+    // 份额之和不为10,000、重复的接收方或份额为0时拒绝
+    for (const recipients of [
+      [{ recipient: partnerA.publicKey, shareBps: 7000 }],
+      [
+        { recipient: partnerA.publicKey, shareBps: 5000 },
+        { recipient: partnerA.publicKey, shareBps: 5000 },
+      ],
+      [
+        { recipient: partnerA.publicKey, shareBps: 10000 },
+        { recipient: partnerB.publicKey, shareBps: 0 },
+      ],
+    ]) {
+      try {
+        await setFeeSplit(recipients);
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidFeeSplit");
+      }
+    }
+
+    const signature = await setFeeSplit([
+      { recipient: partnerA.publicKey, shareBps: 7000 },
+      { recipient: partnerB.publicKey, shareBps: 3000 },
+    ]);
+    const updated = (await parseEvents(signature)).find((event) => event.name === "feeSplitUpdated");
+    expect(updated.data.newFeeRecipients[1].recipient.toString()).to.equal(partnerB.publicKey.toString());
+    expect(updated.data.newFeeRecipients[1].shareBps).to.equal(3000);
+
+    // 设置分账表后数字池管理员不能提取lamports
+    try {
+      await program.methods
+        .withdrawFunds(new anchor.BN(1))
+        .accounts({
+          config: configPda,
+          pool: splitPoolPda,
+          authority,
+          treasury: splitTreasury,
+          withdrawalLog: poolChildPda("withdrawal_log", splitPoolPda),
+          recipient: authority,
+        })
+        .rpc();
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("FeeSplitActive");
+    }
+
+    // 没有进行中的轮次，费用不计入奖池
+    // 只传入A：可分配的300,000 lamports按70%/30%记账，A立即到账，B的份额暂存在配置账户中
+    const balanceA = await provider.connection.getBalance(partnerA.publicKey);
+    const balanceB = await provider.connection.getBalance(partnerB.publicKey);
+    const treasuryInfo = await provider.connection.getAccountInfo(splitTreasury);
+    const rentExemptMinimum = await provider.connection.getMinimumBalanceForRentExemption(
+      treasuryInfo.data.length
+    );
+    expect(treasuryInfo.lamports - rentExemptMinimum).to.equal(300000);
+
+    let distributed = (await parseEvents(await distribute([partnerA.publicKey]))).find(
+      (event) => event.name === "feesDistributed"
+    );
+    expect(distributed.data.amount.toNumber()).to.equal(300000);
+    expect(distributed.data.paid.toNumber()).to.equal(210000);
+    expect(distributed.data.feeAccrued[1].toNumber()).to.equal(90000);
+    expect(await provider.connection.getBalance(partnerA.publicKey)).to.equal(balanceA + 210000);
+    expect(await provider.connection.getBalance(partnerB.publicKey)).to.equal(balanceB);
+    expect(await provider.connection.getBalance(splitTreasury)).to.equal(rentExemptMinimum);
+
+    // 还有待支付金额时不能修改或取消分账表，即使接收方不变
+    for (const recipients of [
+      [{ recipient: partnerA.publicKey, shareBps: 10000 }],
+      [
+        { recipient: partnerA.publicKey, shareBps: 5000 },
+        { recipient: partnerB.publicKey, shareBps: 5000 },
+      ],
+      [],
+    ]) {
+      try {
+        await setFeeSplit(recipients);
+        expect.fail("应该抛出错误");
+      } catch (error) {
+        expect(error.toString()).to.include("UndistributedFees");
+      }
+    }
+
+    // 财库已无可分配资金时仍可支付B暂存的份额
+    distributed = (await parseEvents(await distribute([partnerA.publicKey, partnerB.publicKey]))).find(
+      (event) => event.name === "feesDistributed"
+    );
+    expect(distributed.data.amount.toNumber()).to.equal(0);
+    expect(distributed.data.paid.toNumber()).to.equal(90000);
+    expect(await provider.connection.getBalance(partnerB.publicKey)).to.equal(balanceB + 90000);
+
+    // 没有任何可分配或待支付的资金时拒绝
+    try {
+      await distribute([partnerA.publicKey, partnerB.publicKey]);
+      expect.fail("应该抛出错误");
+    } catch (error) {
+      expect(error.toString()).to.include("NothingToDistribute");
+    }
+
+    // 清空分账表后恢复由数字池管理员提取
+    await setFeeSplit([]);
+    const config = await program.account.luckSnakeConfig.fetch(configPda);
+    expect(config.feeRecipients.every((entry) => entry.shareBps === 0)).to.equal(true);
+  });
+});